@@ -0,0 +1,36 @@
+//! Records the git revision the binary is built from, for the benchmark history.
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=src");
+
+    let Some(git_dir) = git(&["rev-parse", "--git-dir"]) else {
+        return;
+    };
+    // HEAD moves on checkout, the branch ref on commit, and the index on both
+    println!("cargo:rerun-if-changed={git_dir}/HEAD");
+    println!("cargo:rerun-if-changed={git_dir}/index");
+    if let Some(branch) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        println!("cargo:rerun-if-changed={git_dir}/{branch}");
+    }
+
+    let Some(mut revision) = git(&["rev-parse", "--short", "HEAD"]) else {
+        return;
+    };
+    if git(&["status", "--porcelain", "--untracked-files=no"]).is_some_and(|s| !s.is_empty()) {
+        revision.push_str("-dirty");
+    }
+    println!("cargo:rustc-env=GIT_REVISION={revision}");
+}
+
+/// The trimmed output of a successful git command.
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
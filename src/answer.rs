@@ -0,0 +1,200 @@
+use std::{convert::Infallible, fmt, iter::Sum, ops::Add, str::FromStr};
+
+use num_bigint::BigInt;
+
+/// The answer to one part of a puzzle.
+///
+/// Numeric answers compare equal by value whatever their width, so `Answer::U32(3)` equals
+/// `Answer::Usize(3)`.
+#[derive(Debug, Clone)]
+pub enum Answer {
+    U32(u32),
+    U64(u64),
+    Usize(usize),
+    I64(i64),
+    U128(u128),
+    I128(i128),
+    Big(BigInt),
+    Str(String),
+}
+
+impl Answer {
+    /// Widens a numeric answer so that answers of different widths can be compared.
+    pub fn to_big(&self) -> Option<BigInt> {
+        match self {
+            Answer::U32(num) => Some((*num).into()),
+            Answer::U64(num) => Some((*num).into()),
+            Answer::Usize(num) => Some((*num).into()),
+            Answer::I64(num) => Some((*num).into()),
+            Answer::U128(num) => Some((*num).into()),
+            Answer::I128(num) => Some((*num).into()),
+            Answer::Big(num) => Some(num.clone()),
+            Answer::Str(_) => None,
+        }
+    }
+}
+
+/// Integers become [`Answer::Big`] so they compare equal to an answer of any width, anything else
+/// is a string.
+impl FromStr for Answer {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse::<BigInt>() {
+            Ok(num) => Answer::Big(num),
+            Err(_) => Answer::Str(s.to_string()),
+        })
+    }
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Answer::Str(a), Answer::Str(b)) => a == b,
+            (a, b) => a.to_big().is_some() && a.to_big() == b.to_big(),
+        }
+    }
+}
+
+impl Eq for Answer {}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::U32(num) => write!(f, "{num}"),
+            Answer::U64(num) => write!(f, "{num}"),
+            Answer::Usize(num) => write!(f, "{num}"),
+            Answer::I64(num) => write!(f, "{num}"),
+            Answer::U128(num) => write!(f, "{num}"),
+            Answer::I128(num) => write!(f, "{num}"),
+            Answer::Big(num) => write!(f, "{num}"),
+            Answer::Str(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Answer {
+                fn from(value: $ty) -> Self {
+                    Answer::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_from! {
+    u32 => U32,
+    u64 => U64,
+    usize => Usize,
+    i64 => I64,
+    u128 => U128,
+    i128 => I128,
+    BigInt => Big,
+    String => Str,
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Str(value.to_string())
+    }
+}
+
+/// A sum that switches to a [`BigInt`] rather than overflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Total {
+    Small(u128),
+    Big(BigInt),
+}
+
+impl Default for Total {
+    fn default() -> Self {
+        Total::Small(0)
+    }
+}
+
+impl Add for Total {
+    type Output = Total;
+
+    fn add(self, other: Total) -> Total {
+        match (self, other) {
+            (Total::Small(a), Total::Small(b)) => match a.checked_add(b) {
+                Some(sum) => Total::Small(sum),
+                None => Total::Big(BigInt::from(a) + b),
+            },
+            (Total::Big(a), Total::Small(b)) | (Total::Small(b), Total::Big(a)) => {
+                Total::Big(a + b)
+            }
+            (Total::Big(a), Total::Big(b)) => Total::Big(a + b),
+        }
+    }
+}
+
+impl From<u128> for Total {
+    fn from(value: u128) -> Self {
+        Total::Small(value)
+    }
+}
+
+// rayon sums with both of these: the items of each piece, then the pieces
+impl Sum<u128> for Total {
+    fn sum<I: Iterator<Item = u128>>(iter: I) -> Self {
+        iter.map(Total::Small).sum()
+    }
+}
+
+impl Sum<Total> for Total {
+    fn sum<I: Iterator<Item = Total>>(iter: I) -> Self {
+        iter.fold(Total::default(), Add::add)
+    }
+}
+
+impl From<Total> for Answer {
+    fn from(total: Total) -> Self {
+        match total {
+            Total::Small(num) => Answer::U128(num),
+            Total::Big(num) => Answer::Big(num),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_widths_compare_by_value() {
+        assert_eq!(Answer::U32(31), Answer::Usize(31));
+        assert_eq!(Answer::I128(-4), Answer::Big(BigInt::from(-4)));
+        assert_ne!(Answer::U64(1), Answer::U64(2));
+    }
+
+    #[test]
+    fn strings_never_equal_numbers() {
+        assert_eq!(Answer::from("abc"), Answer::from("abc"));
+        assert_ne!(Answer::from("31"), Answer::U32(31));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Answer::U32(11), "11".parse().unwrap());
+        assert_eq!(Answer::from("1,2"), "1,2".parse().unwrap());
+    }
+
+    #[test]
+    fn total_widens_on_overflow() {
+        let total: Total = [u128::MAX, 1, 2].into_iter().sum();
+        assert_eq!(Total::Big(BigInt::from(u128::MAX) + 3), total);
+        assert_eq!(Total::Small(7), [3u128, 4].into_iter().sum());
+        assert_eq!(Answer::U32(7), Total::Small(7).into());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("11387", Answer::Usize(11387).to_string());
+        assert_eq!("-7", Answer::I64(-7).to_string());
+        assert_eq!("1,2,3", Answer::from("1,2,3").to_string());
+    }
+}
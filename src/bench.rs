@@ -0,0 +1,274 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    parse::{Mode, ParseError},
+    solution::{Day, Part},
+    timing,
+};
+
+/// What was measured: parsing the input, or solving one part from an already parsed input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    Parse,
+    Part(Part),
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part(part) => write!(f, "{part}"),
+        }
+    }
+}
+
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parse" => Ok(Stage::Parse),
+            "1" => Ok(Stage::Part(Part::One)),
+            "2" => Ok(Stage::Part(Part::Two)),
+            _ => Err(format!("unknown stage `{s}`")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Returns `None` if there are no samples.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let mut samples = samples.to_vec();
+        samples.sort();
+
+        let runs = samples.len();
+        let min = *samples.first()?;
+        let median = if runs.is_multiple_of(2) {
+            (samples[runs / 2 - 1] + samples[runs / 2]) / 2
+        } else {
+            samples[runs / 2]
+        };
+
+        let mean = samples.iter().sum::<Duration>() / runs as u32;
+        let variance = if runs > 1 {
+            let mean = mean.as_nanos() as f64;
+            samples
+                .iter()
+                .map(|sample| (sample.as_nanos() as f64 - mean).powi(2))
+                .sum::<f64>()
+                / (runs - 1) as f64
+        } else {
+            0.0
+        };
+
+        Some(Self {
+            runs,
+            min,
+            median,
+            mean,
+            stddev: Duration::from_nanos(variance.sqrt().round() as u64),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Benchmark {
+    pub day: u8,
+    pub stage: Stage,
+    pub stats: Stats,
+}
+
+impl Benchmark {
+    /// Formats the benchmark as a row matching [`CSV_HEADER`], with times in nanoseconds.
+    pub fn to_csv(&self) -> String {
+        let stats = &self.stats;
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.day,
+            self.stage,
+            stats.runs,
+            stats.min.as_nanos(),
+            stats.median.as_nanos(),
+            stats.mean.as_nanos(),
+            stats.stddev.as_nanos()
+        )
+    }
+
+    /// Parses a row written by [`Benchmark::to_csv`].
+    pub fn from_csv(row: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = row.trim().split(',').collect();
+        let [day, stage, runs, min, median, mean, stddev] = fields[..] else {
+            return Err(format!("expected 7 fields, found {}", fields.len()));
+        };
+        let nanos = |field: &str| {
+            field
+                .parse()
+                .map(Duration::from_nanos)
+                .map_err(|_| format!("`{field}` is not a number of nanoseconds"))
+        };
+
+        Ok(Self {
+            day: day.parse().map_err(|_| format!("`{day}` is not a day"))?,
+            stage: stage.parse()?,
+            stats: Stats {
+                runs: runs
+                    .parse()
+                    .map_err(|_| format!("`{runs}` is not a number of runs"))?,
+                min: nanos(min)?,
+                median: nanos(median)?,
+                mean: nanos(mean)?,
+                stddev: nanos(stddev)?,
+            },
+        })
+    }
+}
+
+/// Times parsing and each of `parts` `runs` times, after `warmup` untimed runs of each.
+pub fn bench_day(
+    day: &Day,
+    input: &str,
+    parts: &[Part],
+    warmup: usize,
+    runs: usize,
+    mode: Mode,
+) -> Result<Vec<Benchmark>, ParseError> {
+    // fail before timing anything if the input is malformed
+    let parsed = day.parse(input, mode)?;
+
+    let mut benchmarks = Vec::new();
+
+    let parse_stats = measure(warmup, runs, || {
+        let _ = day.parse(input, mode);
+    });
+    if let Some(stats) = parse_stats {
+        benchmarks.push(Benchmark {
+            day: day.day,
+            stage: Stage::Parse,
+            stats,
+        });
+    }
+
+    for &part in parts {
+        let part_stats = measure(warmup, runs, || {
+            let _ = parsed.solve(part);
+        });
+        if let Some(stats) = part_stats {
+            benchmarks.push(Benchmark {
+                day: day.day,
+                stage: Stage::Part(part),
+                stats,
+            });
+        }
+    }
+
+    Ok(benchmarks)
+}
+
+fn measure(warmup: usize, runs: usize, mut f: impl FnMut()) -> Option<Stats> {
+    for _ in 0..warmup {
+        f();
+    }
+    let samples: Vec<Duration> = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+    Stats::from_samples(&samples)
+}
+
+pub const CSV_HEADER: &str = "day,stage,runs,min_ns,median_ns,mean_ns,stddev_ns";
+
+/// Writes one CSV row per benchmark, with times in nanoseconds.
+pub fn write_csv(mut w: impl Write, benchmarks: &[Benchmark]) -> io::Result<()> {
+    writeln!(w, "{CSV_HEADER}")?;
+    for bench in benchmarks {
+        writeln!(w, "{}", bench.to_csv())?;
+    }
+    Ok(())
+}
+
+pub fn table(benchmarks: &[Benchmark]) -> String {
+    let rows: Vec<Vec<String>> = benchmarks
+        .iter()
+        .map(|bench| {
+            let stats = &bench.stats;
+            vec![
+                bench.day.to_string(),
+                bench.stage.to_string(),
+                stats.runs.to_string(),
+                timing::format_duration(Some(stats.min)),
+                timing::format_duration(Some(stats.median)),
+                timing::format_duration(Some(stats.mean)),
+                timing::format_duration(Some(stats.stddev)),
+            ]
+        })
+        .collect();
+    timing::render(
+        &["day", "stage", "runs", "min", "median", "mean", "stddev"],
+        &rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(samples: &[u64]) -> Vec<Duration> {
+        samples.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn stats_odd() {
+        let stats = Stats::from_samples(&millis(&[5, 1, 3])).unwrap();
+        assert_eq!(3, stats.runs);
+        assert_eq!(Duration::from_millis(1), stats.min);
+        assert_eq!(Duration::from_millis(3), stats.median);
+        assert_eq!(Duration::from_millis(3), stats.mean);
+        assert_eq!(Duration::from_millis(2), stats.stddev);
+    }
+
+    #[test]
+    fn stats_even() {
+        let stats = Stats::from_samples(&millis(&[4, 1, 2, 3])).unwrap();
+        assert_eq!(Duration::from_micros(2500), stats.median);
+    }
+
+    #[test]
+    fn stats_empty() {
+        assert_eq!(None, Stats::from_samples(&[]));
+    }
+
+    #[test]
+    fn csv() {
+        let benchmarks = [Benchmark {
+            day: 6,
+            stage: Stage::Part(Part::Two),
+            stats: Stats::from_samples(&millis(&[2])).unwrap(),
+        }];
+        let mut out = Vec::new();
+        write_csv(&mut out, &benchmarks).unwrap();
+        let expected = format!("{CSV_HEADER}\n6,2,1,2000000,2000000,2000000,0\n");
+        assert_eq!(expected, String::from_utf8(out).unwrap());
+
+        assert_eq!(
+            Ok(benchmarks[0].clone()),
+            Benchmark::from_csv(&benchmarks[0].to_csv())
+        );
+    }
+}
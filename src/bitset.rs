@@ -0,0 +1,97 @@
+/// A fixed size set of small integers, one bit each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl BitSet {
+    /// An empty set that can hold `0..capacity`.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            words: vec![0; capacity.div_ceil(64)],
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Adds `idx`, returning whether it's new.
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is out of range.
+    pub fn insert(&mut self, idx: usize) -> bool {
+        assert!(idx < self.capacity, "{idx} out of range");
+        let (word, bit) = (idx / 64, 1 << (idx % 64));
+        let new = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        new
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        idx < self.capacity && self.words[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    /// Removes everything, keeping the capacity.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// The members in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(idx, &word)| {
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                (rest != 0).then(|| {
+                    let bit = rest.trailing_zeros() as usize;
+                    rest &= rest - 1;
+                    idx * 64 + bit
+                })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_and_clear() {
+        let mut set = BitSet::new(130);
+        assert!(set.is_empty());
+        assert!(set.insert(0));
+        assert!(set.insert(129));
+        assert!(set.insert(64));
+        assert!(!set.insert(64));
+        assert!(set.contains(129));
+        assert!(!set.contains(63));
+        assert!(!set.contains(130));
+        assert_eq!(3, set.len());
+        assert_eq!(vec![0, 64, 129], set.iter().collect::<Vec<_>>());
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(130, set.capacity());
+    }
+
+    #[test]
+    #[should_panic = "130 out of range"]
+    fn insert_out_of_range() {
+        BitSet::new(130).insert(130);
+    }
+}
@@ -0,0 +1,116 @@
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use crate::{answer::Answer, solution::Part};
+
+/// Known answers, keyed by input name, day and part.
+///
+/// The file is CSV with the columns `input,day,part,answer`, where `input` is the name the input
+/// is loaded under (`inputs/6`, or `-` for stdin). Everything after the third comma is the answer,
+/// so string answers may contain commas. Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Default)]
+pub struct Answers {
+    expected: HashMap<(String, u8, Part), Answer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail { expected: Answer },
+    Missing,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail { expected } => write!(f, "FAIL, expected {expected}"),
+            Outcome::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+impl Answers {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read answers {}: {err}", path.display()))?;
+        Self::parse(&contents).map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut expected = HashMap::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.splitn(4, ',').collect();
+            let [input, day, part, answer] = fields[..] else {
+                return Err(format!("line {}: expected input,day,part,answer", idx + 1));
+            };
+            let day = day
+                .trim()
+                .parse()
+                .map_err(|_| format!("line {}: `{day}` is not a day", idx + 1))?;
+            let part = match part.trim() {
+                "1" => Part::One,
+                "2" => Part::Two,
+                _ => return Err(format!("line {}: `{part}` is not a part", idx + 1)),
+            };
+            let Ok(answer) = answer.trim().parse();
+            expected.insert((input.trim().to_string(), day, part), answer);
+        }
+        Ok(Self { expected })
+    }
+
+    pub fn check(&self, input: &str, day: u8, part: Part, actual: &Answer) -> Outcome {
+        match self.expected.get(&(input.to_string(), day, part)) {
+            Some(expected) if expected == actual => Outcome::Pass,
+            Some(expected) => Outcome::Fail {
+                expected: expected.clone(),
+            },
+            None => Outcome::Missing,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWERS: &str = r#"# input,day,part,answer
+inputs/1,1,1,11
+inputs/1,1,2,31
+inputs/9,9,1,1,2,3
+"#;
+
+    #[test]
+    fn outcomes() {
+        let answers = Answers::parse(ANSWERS).unwrap();
+
+        assert_eq!(
+            Outcome::Pass,
+            answers.check("inputs/1", 1, Part::One, &Answer::U32(11))
+        );
+        assert_eq!(
+            Outcome::Fail {
+                expected: Answer::U32(31)
+            },
+            answers.check("inputs/1", 1, Part::Two, &Answer::U32(30))
+        );
+        assert_eq!(
+            Outcome::Missing,
+            answers.check("other/1", 1, Part::One, &Answer::U32(11))
+        );
+        assert_eq!(
+            Outcome::Pass,
+            answers.check("inputs/9", 9, Part::One, &Answer::from("1,2,3"))
+        );
+    }
+
+    #[test]
+    fn invalid_line() {
+        let err = Answers::parse("inputs/1,1,3,11").unwrap_err();
+        assert!(err.starts_with("line 1:"), "{err}");
+    }
+}
@@ -2,28 +2,42 @@ use std::collections::HashMap;
 
 use rayon::prelude::*;
 
-pub fn main() {
-    const INPUT: &str = include_str!("../inputs/1");
-    println!("day 1 part 1: {}", total_distance(INPUT));
-    println!("day 1 part 2: {}", similarity_score(INPUT));
+use crate::{
+    answer::{Answer, Total},
+    parse::{self, Mode, ParseError},
+    solution::{Solution, SolveError},
+};
+
+pub struct Lists {
+    pub left: Vec<u32>,
+    pub right: Vec<u32>,
 }
 
-fn parse_input(input: &str) -> (Vec<u32>, Vec<u32>) {
-    input
-        .par_lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .filter_map(|line| {
-            let (left, right) = line.split_once("   ")?;
-            let left: u32 = left.parse().ok()?;
-            let right: u32 = right.parse().ok()?;
-            Some((left, right))
-        })
-        .unzip()
+impl Solution for Lists {
+    const DAY: u8 = 1;
+
+    fn parse(input: &str, mode: Mode) -> Result<Self, ParseError> {
+        let (left, right) = parse_input(input, mode)?;
+        Ok(Self { left, right })
+    }
+
+    fn part1(&self) -> Result<Answer, SolveError> {
+        Ok(total_distance(&self.left, &self.right).into())
+    }
+
+    fn part2(&self) -> Result<Answer, SolveError> {
+        Ok(similarity_score(&self.left, &self.right).into())
+    }
+}
+
+pub fn parse_input(input: &str, mode: Mode) -> Result<(Vec<u32>, Vec<u32>), ParseError> {
+    let pairs: Vec<(u32, u32)> = parse::lines(input, mode, parse::complete(parse::pair(' ')))?;
+    Ok(pairs.into_par_iter().unzip())
 }
 
-fn total_distance(input: &str) -> u32 {
-    let (mut list1, mut list2): (Vec<_>, Vec<_>) = parse_input(input);
+pub fn total_distance(list1: &[u32], list2: &[u32]) -> Total {
+    let mut list1 = list1.to_vec();
+    let mut list2 = list2.to_vec();
 
     list1.par_sort();
     list2.par_sort();
@@ -31,39 +45,31 @@ fn total_distance(input: &str) -> u32 {
     list1
         .into_par_iter()
         .zip(list2.into_par_iter())
-        .map(|(left, right)| left.abs_diff(right))
+        .map(|(left, right)| left.abs_diff(right) as u128)
         .sum()
 }
 
-fn similarity_score(input: &str) -> u32 {
-    let (list1, list2): (Vec<_>, Vec<_>) = parse_input(input);
-
-    let right_counts = counts(&list2);
+pub fn similarity_score(list1: &[u32], list2: &[u32]) -> Total {
+    let right_counts = counts(list2);
 
     list1
-        .into_par_iter()
-        .map(|num| num * right_counts.get(&num).unwrap_or(&0))
+        .par_iter()
+        .map(|num| *num as u128 * *right_counts.get(num).unwrap_or(&0) as u128)
         .sum()
 }
 
 fn counts(nums: &[u32]) -> HashMap<u32, u32> {
     nums.par_iter()
-        .fold(
-            || HashMap::<u32, u32>::new(),
-            |mut acc, val| {
-                acc.entry(*val).and_modify(|e| *e += 1).or_insert(1);
-                acc
-            },
-        )
-        .reduce(
-            || HashMap::<u32, u32>::new(),
-            |mut acc, val| {
-                for (k, v) in val {
-                    acc.entry(k).and_modify(|e| *e += v).or_insert(v);
-                }
-                acc
-            },
-        )
+        .fold(HashMap::<u32, u32>::new, |mut acc, val| {
+            acc.entry(*val).and_modify(|e| *e += 1).or_insert(1);
+            acc
+        })
+        .reduce(HashMap::<u32, u32>::new, |mut acc, val| {
+            for (k, v) in val {
+                acc.entry(k).and_modify(|e| *e += v).or_insert(v);
+            }
+            acc
+        })
 }
 
 #[cfg(test)]
@@ -79,17 +85,45 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let expected = 11;
-        let actual = total_distance(INPUT);
+        let expected = Answer::U32(11);
+        let actual = Lists::parse(INPUT, Mode::Strict).unwrap().part1().unwrap();
 
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn part_2() {
-        let expected = 31;
-        let actual = similarity_score(INPUT);
+        let expected = Answer::U32(31);
+        let actual = Lists::parse(INPUT, Mode::Strict).unwrap().part2().unwrap();
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn large_totals() {
+        let big = [u32::MAX; 3];
+        assert_eq!(
+            Total::Small(3 * u32::MAX as u128),
+            total_distance(&big, &[0; 3])
+        );
+        assert_eq!(
+            Total::Small(9 * u32::MAX as u128),
+            similarity_score(&big, &big)
+        );
+    }
+
+    #[test]
+    fn any_whitespace_separates() {
+        let (left, right) = parse_input("3 4\n5\t\t6\n", Mode::Strict).unwrap();
+        assert_eq!((vec![3, 5], vec![4, 6]), (left, right));
+    }
+
+    #[test]
+    fn strict_rejects_malformed_line() {
+        let err = parse_input("3   4\n4   x\n", Mode::Strict).unwrap_err();
+        assert_eq!((2, 5, "x"), (err.line, err.column, err.text.as_str()));
+
+        let (left, right) = parse_input("3   4\n4   x\n", Mode::Lenient).unwrap();
+        assert_eq!((vec![3], vec![4]), (left, right));
+    }
 }
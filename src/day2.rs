@@ -1,27 +1,44 @@
-use rayon::prelude::*;
+use crate::{
+    answer::Answer,
+    parse::{self, Mode, ParseError},
+    solution::{Solution, SolveError},
+};
 
-pub fn main() {
-    const INPUT: &str = include_str!("../inputs/2");
-    println!("day 2 part 1: {}", num_safe_reports(INPUT));
-    println!("day 2 part 2: {}", num_safe_reports_dampened(INPUT));
+pub struct Reports {
+    pub reports: Vec<Vec<usize>>,
 }
 
-fn num_safe_reports(input: &str) -> usize {
-    parse_input(input)
-        .into_iter()
-        .filter(|report| is_safe(report))
-        .count()
+impl Solution for Reports {
+    const DAY: u8 = 2;
+
+    fn parse(input: &str, mode: Mode) -> Result<Self, ParseError> {
+        Ok(Self {
+            reports: parse_input(input, mode)?,
+        })
+    }
+
+    fn part1(&self) -> Result<Answer, SolveError> {
+        Ok(num_safe_reports(&self.reports).into())
+    }
+
+    fn part2(&self) -> Result<Answer, SolveError> {
+        Ok(num_safe_reports_dampened(&self.reports).into())
+    }
+}
+
+pub fn num_safe_reports(reports: &[Vec<usize>]) -> usize {
+    reports.iter().filter(|report| is_safe(report)).count()
 }
 
-fn num_safe_reports_dampened(input: &str) -> usize {
-    parse_input(input)
-        .into_iter()
-        .map(dampen)
+pub fn num_safe_reports_dampened(reports: &[Vec<usize>]) -> usize {
+    reports
+        .iter()
+        .map(|report| dampen(report))
         .filter(|damp_reports| damp_reports.iter().any(|report| is_safe(report)))
         .count()
 }
 
-fn is_safe(levels: &[usize]) -> bool {
+pub fn is_safe(levels: &[usize]) -> bool {
     let all_increasing = || {
         levels.windows(2).all(|vals| {
             let [a, b, ..] = vals else {
@@ -46,37 +63,20 @@ fn is_safe(levels: &[usize]) -> bool {
         a.abs_diff(*b)
     });
 
-    (all_increasing() || all_decreasing()) && diffs.all(|diff| diff >= 1 && diff <= 3)
+    (all_increasing() || all_decreasing()) && diffs.all(|diff| (1..=3).contains(&diff))
 }
 
-fn parse_input(input: &str) -> Vec<Vec<usize>> {
-    input
-        .par_lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            let levels = line
-                .split(' ')
-                .filter_map(|num| num.parse().ok())
-                .collect::<Vec<usize>>();
-            levels
-        })
-        .collect()
+pub fn parse_input(input: &str, mode: Mode) -> Result<Vec<Vec<usize>>, ParseError> {
+    parse::lines(input, mode, parse::complete(parse::integers))
 }
 
-fn dampen(levels: Vec<usize>) -> Vec<Vec<usize>> {
+pub fn dampen(levels: &[usize]) -> Vec<Vec<usize>> {
     (0..levels.len())
         .map(|idx| {
             levels
                 .iter()
                 .enumerate()
-                .filter_map(|(i, level)| {
-                    if i == idx {
-                        return None;
-                    } else {
-                        return Some(*level);
-                    }
-                })
+                .filter_map(|(i, level)| if i == idx { None } else { Some(*level) })
                 .collect::<Vec<_>>()
         })
         .collect()
@@ -95,16 +95,22 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let expected = 2;
-        let actual = num_safe_reports(INPUT);
+        let expected = Answer::Usize(2);
+        let actual = Reports::parse(INPUT, Mode::Strict)
+            .unwrap()
+            .part1()
+            .unwrap();
 
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn part_2() {
-        let expected = 4;
-        let actual = num_safe_reports_dampened(INPUT);
+        let expected = Answer::Usize(4);
+        let actual = Reports::parse(INPUT, Mode::Strict)
+            .unwrap()
+            .part2()
+            .unwrap();
 
         assert_eq!(expected, actual);
     }
@@ -1,89 +1,98 @@
+use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::character::complete::{digit0, digit1};
-use nom::combinator::{map_res, recognize};
-use nom::sequence::tuple;
+use nom::combinator::value;
 use nom::IResult;
 
-pub fn main() {
-    const INPUT: &str = include_str!("../inputs/3");
-    println!("day 3 part 1: {}", uncorrupted_mul_sum(INPUT));
-    println!("day 3 part 2: {}", uncorrupted_conditional_mul_sum(INPUT));
+use crate::{
+    answer::{Answer, Total},
+    parse::{self, Mode, ParseError},
+    solution::{Solution, SolveError},
+};
+
+pub struct Program {
+    pub instructions: Vec<Instruction>,
 }
 
-fn uncorrupted_mul_sum(input: &str) -> usize {
-    let mut total = 0;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(usize, usize),
+    Do,
+    Dont,
+}
 
-    let mut remaining = Some(input);
-    while let Some(input) = remaining {
-        match mul(input) {
-            Ok((input, (num1, num2))) => {
-                total += num1 * num2;
-                remaining = Some(input);
-            }
-            Err(_) => remaining = input.get(1..),
-        }
+impl Solution for Program {
+    const DAY: u8 = 3;
+
+    /// Corrupted memory is expected, so parsing never fails.
+    fn parse(input: &str, _mode: Mode) -> Result<Self, ParseError> {
+        Ok(Self {
+            instructions: parse_input(input),
+        })
     }
 
-    total
+    fn part1(&self) -> Result<Answer, SolveError> {
+        Ok(uncorrupted_mul_sum(&self.instructions).into())
+    }
+
+    fn part2(&self) -> Result<Answer, SolveError> {
+        Ok(uncorrupted_conditional_mul_sum(&self.instructions).into())
+    }
+}
+
+/// Scans the corrupted memory for instructions, skipping anything that isn't one.
+pub fn parse_input(input: &str) -> Vec<Instruction> {
+    parse::scan(input, instruction)
+}
+
+/// Two `usize`s always multiply within a `u128`.
+fn product(num1: usize, num2: usize) -> u128 {
+    num1 as u128 * num2 as u128
 }
 
-fn uncorrupted_conditional_mul_sum(input: &str) -> usize {
-    let mut total = 0;
+pub fn uncorrupted_mul_sum(instructions: &[Instruction]) -> Total {
+    instructions
+        .iter()
+        .map(|instruction| match *instruction {
+            Instruction::Mul(num1, num2) => product(num1, num2),
+            Instruction::Do | Instruction::Dont => 0,
+        })
+        .sum()
+}
+
+pub fn uncorrupted_conditional_mul_sum(instructions: &[Instruction]) -> Total {
+    let mut total = Total::default();
 
     let mut mul_state = State::Enabled;
-    let mut remaining = Some(input);
-    while let Some(input) = remaining {
-        match mul_state {
-            State::Enabled => {
-                if let Ok((input, (num1, num2))) = mul(input) {
-                    remaining = Some(input);
-                    total += num1 * num2;
-                    continue;
-                }
-                if let Ok((input, ())) = disable(input) {
-                    remaining = Some(input);
-                    mul_state = State::Disabled;
-                    continue;
-                }
-                remaining = input.get(1..);
+    for instruction in instructions {
+        match (*instruction, &mul_state) {
+            (Instruction::Mul(num1, num2), State::Enabled) => {
+                total = total + product(num1, num2).into()
             }
-            State::Disabled => match enable(input) {
-                Ok((input, ())) => {
-                    remaining = Some(input);
-                    mul_state = State::Enabled;
-                }
-                Err(_) => remaining = input.get(1..),
-            },
+            (Instruction::Mul(..), State::Disabled) => {}
+            (Instruction::Do, _) => mul_state = State::Enabled,
+            (Instruction::Dont, _) => mul_state = State::Disabled,
         }
     }
 
     total
 }
 
-fn mul(input: &str) -> IResult<&str, (usize, usize)> {
+fn instruction(input: &str) -> IResult<&str, Instruction> {
+    alt((
+        mul,
+        value(Instruction::Dont, tag("don't()")),
+        value(Instruction::Do, tag("do()")),
+    ))(input)
+}
+
+fn mul(input: &str) -> IResult<&str, Instruction> {
     let (input, _) = tag("mul(")(input)?;
-    let (input, num1) = number(input)?;
+    let (input, num1) = parse::integer(input)?;
     let (input, _) = tag(",")(input)?;
-    let (input, num2) = number(input)?;
+    let (input, num2) = parse::integer(input)?;
     let (input, _) = tag(")")(input)?;
 
-    Ok((input, (num1, num2)))
-}
-
-fn number(input: &str) -> IResult<&str, usize> {
-    map_res(recognize(tuple((digit1, digit0, digit0))), |s: &str| {
-        s.parse()
-    })(input)
-}
-
-fn enable(input: &str) -> IResult<&str, ()> {
-    let (remaining, _) = tag("do()")(input)?;
-    Ok((remaining, ()))
-}
-
-fn disable(input: &str) -> IResult<&str, ()> {
-    let (remaining, _) = tag("don't()")(input)?;
-    Ok((remaining, ()))
+    Ok((input, Instruction::Mul(num1, num2)))
 }
 
 enum State {
@@ -94,6 +103,7 @@ enum State {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use num_bigint::BigInt;
 
     const INPUT1: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
     const INPUT2: &str =
@@ -101,16 +111,37 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let expected = 161;
-        let actual = uncorrupted_mul_sum(INPUT1);
+        let expected = Answer::Usize(161);
+        let actual = Program::parse(INPUT1, Mode::Strict)
+            .unwrap()
+            .part1()
+            .unwrap();
 
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn large_products() {
+        let max = usize::MAX.to_string();
+        let input = format!("mul({max},{max})mul({max},{max})");
+        let square = usize::MAX as u128 * usize::MAX as u128;
+        let expected = BigInt::from(square) * 2;
+        assert_eq!(
+            Answer::Big(expected),
+            Program::parse(&input, Mode::Strict)
+                .unwrap()
+                .part1()
+                .unwrap()
+        );
+    }
+
     #[test]
     fn part_2() {
-        let expected = 48;
-        let actual = uncorrupted_conditional_mul_sum(INPUT2);
+        let expected = Answer::Usize(48);
+        let actual = Program::parse(INPUT2, Mode::Strict)
+            .unwrap()
+            .part2()
+            .unwrap();
 
         assert_eq!(expected, actual);
     }
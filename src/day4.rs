@@ -1,131 +1,131 @@
-pub fn main() {
-    const INPUT: &str = include_str!("../inputs/4");
-    println!("day 4 part 1: {}", word_search_total(INPUT, "XMAS"));
-    println!("day 4 part 2: {}", crossed_mas_total(INPUT));
+use std::collections::HashSet;
+
+use crate::{
+    answer::Answer,
+    direction::Direction8,
+    grid::{Grid, Position},
+    parse::{self, Mode, ParseError},
+    solution::{Part, Solution, SolveError},
+    visualize::{self, Picture, Style},
+};
+
+pub struct WordSearch {
+    letters: Grid<char>,
 }
 
-struct Grid {
-    letters: Vec<char>,
-    width: usize,
-}
+impl Solution for WordSearch {
+    const DAY: u8 = 4;
 
-impl Grid {
-    fn from_input(input: &str) -> Self {
-        let width = input.chars().take_while(|c| *c != '\n').count();
-        let letters = input.chars().filter(|c| *c != '\n').collect();
-        Self { width, letters }
+    fn parse(input: &str, _mode: Mode) -> Result<Self, ParseError> {
+        Self::from_input(input)
     }
 
-    fn surrounding_words(&self, idx: usize, len: usize) -> [String; 8] {
-        let pos = self.position(idx);
-
-        // north
-        let north: String = ((pos.row.saturating_sub(len - 1))..=pos.row)
-            .rev()
-            .filter_map(|row| self.get_xy(pos.column, row))
-            .collect();
-
-        // north east
-        let north_east: String = ((pos.row.saturating_sub(len - 1))..=pos.row)
-            .rev()
-            .zip(pos.column..(pos.column + len))
-            .filter_map(|(row, column)| self.get_xy(column, row))
-            .collect();
-
-        // east
-        let east: String = (pos.column..(pos.column + len))
-            .filter_map(|column| self.get_xy(column, pos.row))
-            .collect();
-
-        // south east
-        let south_east: String = (pos.row..(pos.row + len))
-            .zip(pos.column..(pos.column + len))
-            .filter_map(|(row, column)| self.get_xy(column, row))
-            .collect();
-
-        // south
-        let south: String = (pos.row..(pos.row + len))
-            .filter_map(|row| self.get_xy(pos.column, row))
-            .collect();
+    fn part1(&self) -> Result<Answer, SolveError> {
+        Ok(word_search_total(self, "XMAS").into())
+    }
 
-        // south west
-        let south_west: String = (pos.row..(pos.row + len))
-            .zip((pos.column.saturating_sub(len - 1)..=pos.column).rev())
-            .filter_map(|(row, column)| self.get_xy(column, row))
-            .collect();
+    fn part2(&self) -> Result<Answer, SolveError> {
+        Ok(crossed_mas_total(self).into())
+    }
 
-        // west
-        let west: String = (pos.column.saturating_sub(len - 1)..=pos.column)
-            .rev()
-            .filter_map(|column| self.get_xy(column, pos.row))
-            .collect();
+    fn visualize(&self, part: Part) -> Option<Picture> {
+        let found = match part {
+            Part::One => self.word_positions("XMAS"),
+            Part::Two => self.crossed_mas_positions(),
+        };
+        Some(visualize::draw(&self.letters, |pos, c| {
+            let style = if found.contains(&pos) {
+                Style::Highlight
+            } else {
+                Style::Dim
+            };
+            (*c, style)
+        }))
+    }
+}
 
-        // north west
-        let north_west: String = (pos.row.saturating_sub(len - 1)..=pos.row)
-            .rev()
-            .zip((pos.column.saturating_sub(len - 1)..=pos.column).rev())
-            .filter_map(|(row, column)| self.get_xy(column, row))
-            .collect();
+impl WordSearch {
+    pub fn from_input(input: &str) -> Result<Self, ParseError> {
+        let letters = parse::grid(input, |_, c| c)?;
+        Ok(Self { letters })
+    }
 
-        [
-            north, north_east, east, south_east, south, south_west, west, north_west,
-        ]
+    pub fn letters(&self) -> &Grid<char> {
+        &self.letters
     }
 
-    fn crossed_words(&self, idx: usize) -> [String; 2] {
-        let pos = self.position(idx);
-        let row = pos.row as isize;
-        let column = pos.column as isize;
-
-        // angled from top left to bottom right
-        let top_left_bottom_right: String = ((row - 1)..=(row + 1))
-            .zip((column - 1)..=(column + 1))
-            .filter_map(|(row, column)| {
-                let row = row.try_into().ok()?;
-                let column = column.try_into().ok()?;
-                self.get_xy(column, row)
-            })
-            .collect();
+    /// The words of up to `len` letters starting at `idx`, clockwise from north. Words are cut
+    /// short at the edge of the grid.
+    pub fn surrounding_words(&self, idx: usize, len: usize) -> [String; 8] {
+        let pos = self.letters.position(idx);
+        Direction8::ALL.map(|direction| {
+            self.letters
+                .ray(pos, direction.offset())
+                .take(len)
+                .map(|(_, c)| *c)
+                .collect()
+        })
+    }
 
-        // angled from top right to bottom left
-        let top_right_bottom_left: String = ((row - 1)..=(row + 1))
-            .zip(((column - 1)..=(column + 1)).rev())
-            .filter_map(|(row, column)| {
-                let row = row.try_into().ok()?;
-                let column = column.try_into().ok()?;
-                self.get_xy(column, row)
-            })
-            .collect();
+    pub fn crossed_words(&self, idx: usize) -> [String; 2] {
+        let pos = self.letters.position(idx);
 
-        [top_left_bottom_right, top_right_bottom_left]
+        // angled from top left to bottom right, then from top right to bottom left
+        [Direction8::SouthEast, Direction8::SouthWest].map(|direction| {
+            [pos + direction.reverse(), pos, pos + direction]
+                .into_iter()
+                .filter_map(|pos| self.letters.get(pos))
+                .collect()
+        })
     }
 
-    fn position(&self, idx: usize) -> Position {
-        let row = idx / self.width;
-        let column = idx - (row * self.width);
-        Position { column, row }
+    /// Whether the letter at `idx` is the middle of two crossed `MAS`.
+    pub fn is_crossed_mas(&self, idx: usize) -> bool {
+        self.crossed_words(idx)
+            .into_iter()
+            .all(|word| word == "MAS" || word == "SAM")
     }
 
-    fn get(&self, pos: &Position) -> Option<char> {
-        if pos.column >= self.width {
-            return None;
+    /// Every letter that's part of an occurrence of `needle`, in any direction.
+    pub fn word_positions(&self, needle: &str) -> HashSet<Position> {
+        let len = needle.chars().count();
+        let mut found = HashSet::new();
+        for pos in self.letters.positions() {
+            for direction in Direction8::ALL {
+                let cells: Vec<(Position, &char)> = self
+                    .letters
+                    .ray(pos, direction.offset())
+                    .take(len)
+                    .collect();
+                if cells.iter().map(|(_, c)| **c).eq(needle.chars()) {
+                    found.extend(cells.iter().map(|(pos, _)| *pos));
+                }
+            }
         }
-        self.letters.get(pos.row * self.width + pos.column).copied()
+        found
     }
 
-    fn get_xy(&self, column: usize, row: usize) -> Option<char> {
-        self.get(&Position { column, row })
+    /// Every letter that's part of two crossed `MAS`.
+    pub fn crossed_mas_positions(&self) -> HashSet<Position> {
+        (0..self.letters.len())
+            .filter(|&idx| self.is_crossed_mas(idx))
+            .flat_map(|idx| {
+                let pos = self.letters.position(idx);
+                [
+                    Direction8::NorthWest,
+                    Direction8::NorthEast,
+                    Direction8::SouthEast,
+                    Direction8::SouthWest,
+                ]
+                .map(|direction| pos + direction)
+                .into_iter()
+                .chain([pos])
+            })
+            .collect()
     }
 }
 
-#[derive(Debug)]
-struct Position {
-    pub column: usize,
-    pub row: usize,
-}
-
-fn word_search_total(input: &str, needle: &str) -> usize {
-    let grid = Grid::from_input(input);
+pub fn word_search_total(grid: &WordSearch, needle: &str) -> usize {
     (0..grid.letters.len())
         .map(|idx| {
             grid.surrounding_words(idx, needle.len())
@@ -136,15 +136,9 @@ fn word_search_total(input: &str, needle: &str) -> usize {
         .sum()
 }
 
-fn crossed_mas_total(input: &str) -> usize {
-    let grid = Grid::from_input(input);
-
+pub fn crossed_mas_total(grid: &WordSearch) -> usize {
     (0..grid.letters.len())
-        .filter(|idx| {
-            grid.crossed_words(*idx)
-                .into_iter()
-                .all(|word| word == "MAS" || word == "SAM")
-        })
+        .filter(|&idx| grid.is_crossed_mas(idx))
         .count()
 }
 
@@ -166,15 +160,18 @@ MXMXAXMASX"#;
 
     #[test]
     fn part_1() {
-        let expected = 18;
-        let actual = word_search_total(INPUT, "XMAS");
+        let expected = Answer::Usize(18);
+        let actual = WordSearch::parse(INPUT, Mode::Strict)
+            .unwrap()
+            .part1()
+            .unwrap();
 
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn north() {
-        let grid = Grid::from_input(INPUT);
+        let grid = WordSearch::from_input(INPUT).unwrap();
         let expected = "MMSS";
         let actual = &grid.surrounding_words(90, expected.len())[0];
 
@@ -183,7 +180,7 @@ MXMXAXMASX"#;
 
     #[test]
     fn north_east() {
-        let grid = Grid::from_input(INPUT);
+        let grid = WordSearch::from_input(INPUT).unwrap();
         let expected = "MAXM";
         let actual = &grid.surrounding_words(90, expected.len())[1];
         assert_eq!(expected, actual);
@@ -195,7 +192,7 @@ MXMXAXMASX"#;
 
     #[test]
     fn east() {
-        let grid = Grid::from_input(INPUT);
+        let grid = WordSearch::from_input(INPUT).unwrap();
         let expected = "MMMS";
         let actual = &grid.surrounding_words(0, expected.len())[2];
         assert_eq!(expected, actual);
@@ -207,7 +204,7 @@ MXMXAXMASX"#;
 
     #[test]
     fn south_east() {
-        let grid = Grid::from_input(INPUT);
+        let grid = WordSearch::from_input(INPUT).unwrap();
         let expected = "MSXM";
         let actual = &grid.surrounding_words(0, expected.len())[3];
 
@@ -216,7 +213,7 @@ MXMXAXMASX"#;
 
     #[test]
     fn south() {
-        let grid = Grid::from_input(INPUT);
+        let grid = WordSearch::from_input(INPUT).unwrap();
         let expected = "MMAM";
         let actual = &grid.surrounding_words(0, expected.len())[4];
 
@@ -225,7 +222,7 @@ MXMXAXMASX"#;
 
     #[test]
     fn south_west() {
-        let grid = Grid::from_input(INPUT);
+        let grid = WordSearch::from_input(INPUT).unwrap();
         let expected = "MSAM";
         let actual = &grid.surrounding_words(9, expected.len())[5];
 
@@ -234,7 +231,7 @@ MXMXAXMASX"#;
 
     #[test]
     fn west() {
-        let grid = Grid::from_input(INPUT);
+        let grid = WordSearch::from_input(INPUT).unwrap();
         let expected = "MSAM";
         let actual = &grid.surrounding_words(9, expected.len())[6];
         assert_eq!(expected, actual);
@@ -246,7 +243,7 @@ MXMXAXMASX"#;
 
     #[test]
     fn north_west() {
-        let grid = Grid::from_input(INPUT);
+        let grid = WordSearch::from_input(INPUT).unwrap();
         let expected = "XMAS";
         let actual = &grid.surrounding_words(99, expected.len())[7];
 
@@ -255,18 +252,43 @@ MXMXAXMASX"#;
 
     #[test]
     fn part_2() {
-        let expected = 9;
-        let actual = crossed_mas_total(INPUT);
+        let expected = Answer::Usize(9);
+        let actual = WordSearch::parse(INPUT, Mode::Strict)
+            .unwrap()
+            .part2()
+            .unwrap();
 
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn found_positions() {
+        let grid = WordSearch::from_input("XMASX\n....M\nS.S.A\n.A..S\nM.M..\n").unwrap();
+        let word: HashSet<Position> = (0..4)
+            .map(|x| Position::new(x, 0))
+            .chain((0..4).map(|y| Position::new(4, y)))
+            .collect();
+        assert_eq!(word, grid.word_positions("XMAS"));
+
+        let cross: HashSet<Position> = [(0, 2), (2, 2), (1, 3), (0, 4), (2, 4)]
+            .map(|(x, y)| Position::new(x, y))
+            .into();
+        assert_eq!(cross, grid.crossed_mas_positions());
+    }
+
+    #[test]
+    fn visualize_highlights_matches() {
+        let grid = WordSearch::from_input("XMAS\n").unwrap();
+        let drawn = visualize::ansi(&Solution::visualize(&grid, Part::One).unwrap());
+        assert_eq!(4, drawn.matches("\x1b[1;33m").count());
+    }
+
     #[test_case(11, ["MSX", "MSA"])]
     #[test_case(0, ["MS", "M"])]
     #[test_case(99, ["MX", "X"])]
     #[test_case(44, ["MAX", "SAM"])]
-    fn crossed_words(idx: usize, expected: [&str; 2]) {
-        let grid = Grid::from_input(INPUT);
+    pub fn crossed_words(idx: usize, expected: [&str; 2]) {
+        let grid = WordSearch::from_input(INPUT).unwrap();
         let actual = grid.crossed_words(idx);
         assert_eq!(
             expected, actual,
@@ -1,23 +1,58 @@
-pub fn main() {
-    const INPUT: &str = include_str!("../inputs/5");
-    println!("day 5 part 1: {}", correct_order_middle_page_total(INPUT));
-    println!(
-        "day 5 part 2: {}",
-        incorrect_order_fixed_middle_page_total(INPUT)
-    );
+use std::collections::HashMap;
+
+use crate::{
+    answer::{Answer, Total},
+    graph::{Cycle, Graph},
+    parse::{self, Mode, ParseError},
+    solution::{Solution, SolveError},
+};
+
+pub struct Manual {
+    pub rules: Vec<Rule>,
+    pub updates: Vec<Update>,
+    /// The rules as edges from the page that must come first.
+    pub graph: Graph<usize>,
 }
 
-struct Update {
-    pages: Vec<usize>,
+impl Solution for Manual {
+    const DAY: u8 = 5;
+
+    fn parse(input: &str, mode: Mode) -> Result<Self, ParseError> {
+        let (rules, updates) = parse_input(input, mode)?;
+        let graph = rule_graph(&rules);
+        Ok(Self {
+            rules,
+            updates,
+            graph,
+        })
+    }
+
+    fn part1(&self) -> Result<Answer, SolveError> {
+        Ok(correct_order_middle_page_total(&self.graph, &self.updates).into())
+    }
+
+    fn part2(&self) -> Result<Answer, SolveError> {
+        match incorrect_order_fixed_middle_page_total(&self.graph, &self.updates) {
+            Ok(total) => Ok(total.into()),
+            Err(cycle) => Err(SolveError(format!(
+                "no valid order, the rules form a cycle: {cycle}"
+            ))),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Update {
+    pub pages: Vec<usize>,
 }
 
-struct Rule {
-    before: usize,
-    after: usize,
+pub struct Rule {
+    pub before: usize,
+    pub after: usize,
 }
 
 impl Update {
-    fn satisfies_rule(&self, rule: &Rule) -> bool {
+    pub fn satisfies_rule(&self, rule: &Rule) -> bool {
         let Some(before_page_idx) = self.index_of_page(rule.before) else {
             return true;
         };
@@ -27,7 +62,7 @@ impl Update {
         before_page_idx < after_page_idx
     }
 
-    fn index_of_page(&self, page_num: usize) -> Option<usize> {
+    pub fn index_of_page(&self, page_num: usize) -> Option<usize> {
         self.pages
             .iter()
             .enumerate()
@@ -35,30 +70,31 @@ impl Update {
             .map(|(idx, _page)| idx)
     }
 
-    fn middle_page(&self) -> usize {
+    pub fn middle_page(&self) -> usize {
         self.pages[self.pages.len() / 2]
     }
 
-    fn fix(mut self, rules: &[Rule]) -> Self {
-        // this is lazy, but you know what? it works.
-        while rules.iter().any(|rule| !self.satisfies_rule(rule)) {
-            for idx in 0..self.pages.len() {
-                let page = self.pages[idx];
-
-                for rule in rules {
-                    if rule.after == page {
-                        for after_idx in idx..self.pages.len() {
-                            let page = self.pages[after_idx];
-                            if rule.before == page {
-                                self.pages.swap(idx, after_idx);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    /// Whether every pair of pages is in the order the rules require.
+    pub fn is_ordered(&self, graph: &Graph<usize>) -> bool {
+        self.pages.iter().enumerate().all(|(idx, &page)| {
+            self.pages[..idx]
+                .iter()
+                .all(|&earlier| !graph.has_edge(page, earlier))
+        })
+    }
 
-        self
+    /// Reorders the pages to follow the rules, keeping any repeated pages, failing if the rules
+    /// between them contradict each other.
+    pub fn fix(&self, graph: &Graph<usize>) -> Result<Self, Cycle<usize>> {
+        let rank: HashMap<usize, usize> = graph
+            .topo_sort(&self.pages)?
+            .into_iter()
+            .enumerate()
+            .map(|(rank, page)| (page, rank))
+            .collect();
+        let mut pages = self.pages.clone();
+        pages.sort_by_key(|page| rank[page]);
+        Ok(Self { pages })
     }
 }
 
@@ -70,60 +106,48 @@ impl FromIterator<usize> for Update {
     }
 }
 
-fn correct_order_middle_page_total(input: &str) -> usize {
-    let (rules, updates) = parse_input(input);
+pub fn rule_graph(rules: &[Rule]) -> Graph<usize> {
+    rules.iter().map(|rule| (rule.before, rule.after)).collect()
+}
 
+pub fn correct_order_middle_page_total(graph: &Graph<usize>, updates: &[Update]) -> Total {
     updates
-        .into_iter()
-        .filter_map(|update| {
-            if rules.iter().all(|rule| update.satisfies_rule(rule)) {
-                Some(update.middle_page())
-            } else {
-                None
-            }
-        })
+        .iter()
+        .filter(|update| update.is_ordered(graph))
+        .map(|update| update.middle_page() as u128)
         .sum()
 }
 
-fn incorrect_order_fixed_middle_page_total(input: &str) -> usize {
-    let (rules, updates) = parse_input(input);
-
+pub fn incorrect_order_fixed_middle_page_total(
+    graph: &Graph<usize>,
+    updates: &[Update],
+) -> Result<Total, Cycle<usize>> {
     updates
-        .into_iter()
-        .filter(|update| rules.iter().any(|rule| !update.satisfies_rule(rule)))
-        .map(|update| update.fix(&rules))
-        .map(|update| update.middle_page())
+        .iter()
+        .filter(|update| !update.is_ordered(graph))
+        .map(|update| Ok(update.fix(graph)?.middle_page() as u128))
         .sum()
 }
 
-fn parse_input(input: &str) -> (Vec<Rule>, Vec<Update>) {
-    let lines = input
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty());
-
-    let (rules, updates): (Vec<&str>, Vec<&str>) = lines.partition(|line| is_rule(line));
-
-    let rules = rules
-        .into_iter()
-        .filter_map(|line| {
-            let (before, after) = line.split_once('|')?;
-            let before = before.parse().ok()?;
-            let after = after.parse().ok()?;
-            Some(Rule { before, after })
-        })
-        .collect();
-
-    let updates = updates
-        .into_iter()
-        .map(|line| line.split(',').filter_map(|num| num.parse().ok()).collect())
-        .collect();
-
-    (rules, updates)
-}
-
-fn is_rule(line: &str) -> bool {
-    line.contains('|')
+pub fn parse_input(input: &str, mode: Mode) -> Result<(Vec<Rule>, Vec<Update>), ParseError> {
+    let [rules, updates] = parse::sections(input)?;
+
+    let rules = rules.lines(
+        mode,
+        parse::complete(|line| {
+            let (rest, (before, after)) = parse::pair('|')(line)?;
+            Ok((rest, Rule { before, after }))
+        }),
+    )?;
+    let updates = updates.lines(
+        mode,
+        parse::complete(|line| {
+            let (rest, pages) = parse::list(',')(line)?;
+            Ok((rest, Update { pages }))
+        }),
+    )?;
+
+    Ok((rules, updates))
 }
 
 #[cfg(test)]
@@ -162,15 +186,62 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let expected = 143;
-        let actual = correct_order_middle_page_total(INPUT);
+        let expected = Answer::Usize(143);
+        let actual = Manual::parse(INPUT, Mode::Strict).unwrap().part1().unwrap();
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn part_2() {
-        let expected = 123;
-        let actual = incorrect_order_fixed_middle_page_total(INPUT);
+        let expected = Answer::Usize(123);
+        let actual = Manual::parse(INPUT, Mode::Strict).unwrap().part2().unwrap();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn ordered_agrees_with_rules() {
+        let manual = Manual::parse(INPUT, Mode::Strict).unwrap();
+        for update in &manual.updates {
+            let satisfied = manual.rules.iter().all(|rule| update.satisfies_rule(rule));
+            assert_eq!(satisfied, update.is_ordered(&manual.graph));
+        }
+    }
+
+    #[test]
+    fn fix() {
+        let manual = Manual::parse(INPUT, Mode::Strict).unwrap();
+        let update = Update {
+            pages: vec![97, 13, 75, 29, 47],
+        };
+        let fixed = update.fix(&manual.graph).unwrap();
+        assert_eq!(vec![97, 75, 47, 29, 13], fixed.pages);
+    }
+
+    #[test]
+    fn fix_repeated_pages() {
+        let manual = Manual::parse("1|2\n\n2,1,5,1,3\n", Mode::Strict).unwrap();
+        let fixed = manual.updates[0].fix(&manual.graph).unwrap();
+        assert_eq!(vec![1, 1, 5, 3, 2], fixed.pages);
+        assert_eq!(Answer::U128(5), manual.part2().unwrap());
+    }
+
+    #[test]
+    fn large_totals() {
+        let max = usize::MAX;
+        let input = format!("1|2\n\n9,{max},2\n9,{max},2\n2,1,{max}\n2,1,{max}\n");
+        let manual = Manual::parse(&input, Mode::Strict).unwrap();
+        assert_eq!(Answer::U128(2 * max as u128), manual.part1().unwrap());
+        assert_eq!(Answer::U128(2 * max as u128), manual.part2().unwrap());
+    }
+
+    #[test]
+    fn contradictory_rules() {
+        let manual = Manual::parse("1|2\n2|3\n3|1\n\n1,2,3\n", Mode::Strict).unwrap();
+        assert_eq!(
+            Err(SolveError(
+                "no valid order, the rules form a cycle: 1 -> 2 -> 3 -> 1".to_string()
+            )),
+            manual.part2()
+        );
+    }
 }
@@ -1,222 +1,586 @@
+use itertools::Itertools;
 use rayon::prelude::*;
 use std::{
     collections::HashSet,
-    sync::atomic::{AtomicUsize, Ordering},
+    fmt,
+    hash::{Hash, Hasher},
 };
 
-pub fn main() {
-    const INPUT: &str = include_str!("../inputs/6");
-    println!(
-        "day 6 part 1: {}",
-        Map::from_input(INPUT).num_guard_visited_positions()
-    );
-    println!(
-        "day 6 part 2: {}",
-        Map::from_input(INPUT).num_new_obstacles_for_loop()
-    )
-}
+pub use crate::direction::Direction4 as Heading;
+use crate::{
+    answer::Answer,
+    bitset::BitSet,
+    grid::{Grid, Position},
+    parse::{self, Mode, ParseError},
+    report::Table,
+    simulation::{self, State},
+    solution::{Part, Solution, SolveError},
+    visualize::{self, Picture, Style},
+};
 
 // top left is 0,0
 // up = north = y-1
 // right = east = x+1
+//
+// guards patrol independently and walk through each other
 #[derive(Debug, Clone)]
-struct Map {
-    guard: Guard,
-    visited: HashSet<Position>,
-    visited_direction: HashSet<(Position, Heading)>,
-    obstructions: HashSet<Position>,
-    width: isize,
-    height: isize,
+pub struct Map {
+    guards: Vec<Guard>,
+    /// The positions each guard has been at, indexed like `tiles`.
+    visited: Vec<BitSet>,
+    /// Whether each guard has been found to be walking in a loop.
+    looping: Vec<bool>,
+    tiles: Grid<Tile>,
 }
 
-impl Map {
-    fn from_input(input: &str) -> Self {
-        const OBSTRUCTION: char = '#';
-        const GUARD: char = '^';
-
-        let mut lines = input.lines().map(|line| line.trim()).peekable();
-
-        let width = lines.peek().map(|s| *s).unwrap_or_default().len() as isize;
-        let height = input.len() as isize / (width + 1);
-
-        let mut obstructions = HashSet::new();
-        let mut guard = None;
-        let mut visited = HashSet::new();
-        let mut visited_direction = HashSet::new();
-
-        for (y, line) in lines.enumerate() {
-            for (x, c) in line.char_indices() {
-                let pos = Position {
-                    x: x as _,
-                    y: y as _,
-                };
-                if c == OBSTRUCTION {
-                    obstructions.insert(pos);
-                } else if c == GUARD {
-                    guard = Some(Guard::new(pos.clone(), Heading::North));
-                    visited.insert(pos);
-                    visited_direction.insert((pos, Heading::North));
+impl Solution for Map {
+    const DAY: u8 = 6;
+
+    fn parse(input: &str, _mode: Mode) -> Result<Self, ParseError> {
+        Self::from_input(input)
+    }
+
+    fn part1(&self) -> Result<Answer, SolveError> {
+        Ok(self.clone().num_guard_visited_positions().into())
+    }
+
+    fn part2(&self) -> Result<Answer, SolveError> {
+        Ok(self.clone().num_new_obstacles_for_loop().into())
+    }
+
+    /// The guards' paths, with the loop-causing obstructions for part 2.
+    fn visualize(&self, part: Part) -> Option<Picture> {
+        let mut trail = Trail::new(&self.tiles);
+        for &guard in &self.guards {
+            for guard in self.walk(guard) {
+                trail.add(&guard);
+            }
+        }
+        let candidates = match part {
+            Part::One => HashSet::new(),
+            Part::Two => self.loop_obstacles().into_iter().collect(),
+        };
+        Some(self.draw(&trail, &self.guards, &candidates))
+    }
+
+    /// The guards walking their paths together, one step at a time.
+    fn animate(&self) -> Option<Box<dyn Iterator<Item = Picture> + '_>> {
+        let mut walks: Vec<_> = self.guards.iter().map(|&guard| self.walk(guard)).collect();
+        let mut guards = self.guards.clone();
+        let mut trail = Trail::new(&self.tiles);
+        let frames = std::iter::from_fn(move || {
+            // guards that have left or started going round again stay where they last were
+            let mut moved = false;
+            for (walk, guard) in walks.iter_mut().zip(&mut guards) {
+                if let Some(next) = walk.next() {
+                    *guard = next;
+                    trail.add(guard);
+                    moved = true;
                 }
             }
+            moved.then(|| self.draw(&trail, &guards, &HashSet::new()))
+        });
+        Some(Box::new(frames))
+    }
+
+    /// Every guard's path, and the obstructions found for part 2.
+    fn trace(&self) -> Option<Vec<Table>> {
+        let mut path = Table::new("path", &["guard", "step", "x", "y", "heading", "turned"]);
+        for (idx, &guard) in self.guards.iter().enumerate() {
+            for (number, step) in self.steps(guard).enumerate() {
+                path.push(vec![
+                    (idx as i64).into(),
+                    (number as i64).into(),
+                    (step.position.x as i64).into(),
+                    (step.position.y as i64).into(),
+                    format!("{:?}", step.heading).into(),
+                    step.turned.into(),
+                ]);
+            }
+        }
+
+        let mut obstacles = Table::new("loop-obstacles", &["x", "y"]);
+        for pos in self.loop_obstacles() {
+            obstacles.push(vec![(pos.x as i64).into(), (pos.y as i64).into()]);
         }
+        Some(vec![path, obstacles])
+    }
+}
 
+/// The headings the guard has had at each position, indexed by [`state_index`].
+#[derive(Debug)]
+struct Trail<'a> {
+    tiles: &'a Grid<Tile>,
+    headings: BitSet,
+}
+
+impl<'a> Trail<'a> {
+    fn new(tiles: &'a Grid<Tile>) -> Self {
         Self {
-            guard: guard.unwrap_or_default(),
-            width,
-            height,
-            obstructions,
-            visited,
-            visited_direction,
+            tiles,
+            headings: BitSet::new(tiles.len() * 4),
         }
     }
 
-    fn num_guard_visited_positions(mut self) -> usize {
-        self.move_guard_until_off_map().visited.len()
+    fn add(&mut self, guard: &Guard) {
+        self.headings.insert(state_index(self.tiles, guard));
     }
 
-    // brute force :(
-    fn num_new_obstacles_for_loop(self) -> usize {
-        let success = AtomicUsize::new(0);
+    /// The heading's arrow if the guard only ever faced one way here, otherwise `+`.
+    fn glyph(&self, pos: Position) -> Option<char> {
+        let headings: Vec<Heading> = Heading::all()
+            .filter(|&heading| {
+                let guard = Guard::new(pos, heading);
+                self.headings.contains(state_index(self.tiles, &guard))
+            })
+            .collect();
+        match headings[..] {
+            [] => None,
+            [heading] => Some(heading.glyph()),
+            _ => Some('+'),
+        }
+    }
+}
 
-        let max_moves = self.height * self.width;
-        (0..self.height).par_bridge().for_each(|y| {
-            (0..self.width).par_bridge().for_each(|x| {
-                let pos = Position { x, y };
-                if self.is_obstruction(&pos) || self.guard.position == pos {
-                    return;
-                }
+/// A guard's position and heading as a single index, for sets of guard states.
+///
+/// # Panics
+///
+/// If the guard is off the map.
+fn state_index(tiles: &Grid<Tile>, guard: &Guard) -> usize {
+    let idx = tiles
+        .index(guard.position)
+        .expect("the guard is on the map");
+    idx * 4 + guard.heading as usize
+}
 
-                let mut temp_map = self.clone();
-
-                temp_map.obstructions.insert(pos.clone());
-
-                for _step in 0..max_moves {
-                    let next_pos = temp_map.guard.next_pos();
-
-                    // are we in a loop?
-                    if temp_map
-                        .visited_direction
-                        .contains(&(next_pos.clone(), temp_map.guard.heading))
-                    {
-                        success.fetch_add(1, Ordering::SeqCst);
-                        break;
-                    }
-
-                    if temp_map.is_obstruction(&next_pos) {
-                        temp_map.guard.turn_right();
-                        continue;
-                    }
-                    if temp_map.is_off_map(&next_pos) {
-                        break;
-                    }
-                    temp_map.guard.move_to(&next_pos);
-                    temp_map
-                        .visited_direction
-                        .insert((next_pos, temp_map.guard.heading));
-                }
+impl Map {
+    /// Guards are drawn as an arrow pointing the way they're heading.
+    pub fn from_input(input: &str) -> Result<Self, ParseError> {
+        const OBSTRUCTION: char = '#';
+
+        let mut guards = Vec::new();
+        let tiles = parse::grid(input, |pos, c| {
+            if let Some(heading) = Heading::from_glyph(c) {
+                guards.push(Guard::new(pos, heading));
+            }
+            if c == OBSTRUCTION {
+                Tile::Obstruction
+            } else {
+                Tile::Open
+            }
+        })?;
+
+        if guards.is_empty() {
+            return Err(ParseError {
+                line: tiles.height() + 1,
+                column: 1,
+                text: String::new(),
+                message: "expected a guard, one of `^`, `>`, `v` or `<`".to_string(),
+            });
+        }
+        let visited = guards
+            .iter()
+            .map(|guard| {
+                let mut visited = BitSet::new(tiles.len());
+                visited.insert(tiles.index(guard.position).expect("guards are on the map"));
+                visited
             })
-        });
+            .collect();
+        Ok(Self {
+            looping: vec![false; guards.len()],
+            guards,
+            visited,
+            tiles,
+        })
+    }
+
+    /// The number of positions that any guard visits.
+    pub fn num_guard_visited_positions(mut self) -> usize {
+        self.move_guards_until_off_map().visited().count()
+    }
 
-        success.load(Ordering::Relaxed)
+    pub fn num_new_obstacles_for_loop(self) -> usize {
+        self.loop_obstacles().len()
     }
 
-    fn move_guard_until_off_map(&mut self) -> &mut Self {
-        loop {
-            let next_pos = self.guard.next_pos();
-            if self.is_obstruction(&next_pos) {
-                self.guard.turn_right();
+    /// Every position where one new obstruction would trap the guard in a loop, in row-major
+    /// order.
+    ///
+    /// An obstruction anywhere else than the guard's path changes nothing, and one on the path
+    /// only changes the patrol from the first time the guard would walk into it. So each trial
+    /// starts just before that, and jumps from obstruction to obstruction from there until the
+    /// guard leaves or turns the same way at the same place twice.
+    ///
+    /// With several guards, an obstruction counts if it traps any of the guards that would
+    /// otherwise walk off the map. Nothing can be put where a guard starts.
+    pub fn loop_obstacles(&self) -> Vec<Position> {
+        let mut starts = BitSet::new(self.tiles.len());
+        for guard in &self.guards {
+            starts.insert(self.index(&guard.position));
+        }
+        let mut trials: Vec<(Position, Guard)> = Vec::new();
+        for &guard in &self.guards {
+            if self.is_looping(guard) {
                 continue;
             }
-            if self.is_off_map(&next_pos) {
-                break;
+            let mut seen = starts.clone();
+            trials.extend(
+                self.walk(guard)
+                    .tuple_windows()
+                    .filter(|(_, after)| seen.insert(self.index(&after.position)))
+                    .map(|(before, after)| (after.position, before)),
+            );
+        }
+
+        let obstructions = Obstructions::new(&self.tiles);
+        let mut found: Vec<Position> = trials
+            .into_par_iter()
+            .map_init(
+                || BitSet::new(self.tiles.len() * 4),
+                |turns, (obstruction, guard)| {
+                    turns.clear();
+                    let looped = simulation::states(Jumps::new(&obstructions, obstruction, guard))
+                        .any(|jumps| !turns.insert(state_index(&self.tiles, &jumps.guard)));
+                    looped.then_some(obstruction)
+                },
+            )
+            .flatten()
+            .collect();
+        found.sort_by_key(|pos| (pos.y, pos.x));
+        found.dedup();
+        found
+    }
+
+    /// Moves every guard until it walks off the map, or until it's back where it has already
+    /// been facing the same way and so is walking in a loop.
+    pub fn move_guards_until_off_map(&mut self) -> &mut Self {
+        for idx in 0..self.guards.len() {
+            let path: Vec<Guard> = self.walk(self.guards[idx]).collect();
+            for guard in path {
+                self.guards[idx] = guard;
+                let pos = self.index(&guard.position);
+                self.visited[idx].insert(pos);
             }
-            self.guard.move_to(&next_pos);
-            self.visited.insert(next_pos);
+            self.looping[idx] = self.is_looping(self.guards[idx]);
         }
 
         self
     }
 
-    fn is_obstruction(&self, pos: &Position) -> bool {
-        self.obstructions.contains(pos)
+    /// `guard`'s patrol from where it is now, up to the last state before it walks off the map
+    /// or starts going round a loop again.
+    pub fn walk(&self, guard: Guard) -> impl Iterator<Item = Guard> + '_ {
+        let seen = BitSet::new(self.tiles.len() * 4);
+        simulation::states(Patrol::new(&self.tiles, guard)).scan(seen, |seen, patrol| {
+            seen.insert(state_index(&self.tiles, &patrol.guard))
+                .then_some(patrol.guard)
+        })
+    }
+
+    /// [`Map::walk`] noting where the guard turns.
+    pub fn steps(&self, guard: Guard) -> impl Iterator<Item = Step> + '_ {
+        self.walk(guard).scan(guard.heading, |heading, guard| {
+            let turned = guard.heading != *heading;
+            *heading = guard.heading;
+            Some(Step {
+                position: guard.position,
+                heading: guard.heading,
+                turned,
+            })
+        })
+    }
+
+    /// Whether `guard` will walk in a loop forever rather than leave the map.
+    fn is_looping(&self, guard: Guard) -> bool {
+        self.walk(guard)
+            .last()
+            .is_some_and(|last| Patrol::new(&self.tiles, last).step().is_some())
+    }
+
+    fn draw(&self, trail: &Trail, guards: &[Guard], candidates: &HashSet<Position>) -> Picture {
+        visualize::draw(&self.tiles, |pos, tile| {
+            if let Some(guard) = guards.iter().find(|guard| guard.position == pos) {
+                return (guard.heading.glyph(), Style::Actor);
+            }
+            if candidates.contains(&pos) {
+                return ('O', Style::Candidate);
+            }
+            match (tile, trail.glyph(pos)) {
+                (Tile::Obstruction, _) => ('#', Style::Wall),
+                (Tile::Open, Some(glyph)) => (glyph, Style::Trail),
+                (Tile::Open, None) => ('.', Style::Dim),
+            }
+        })
+    }
+
+    /// Each guard's patrol from where it is now.
+    pub fn patrols(&self) -> impl Iterator<Item = Patrol<'_>> {
+        self.guards
+            .iter()
+            .map(|&guard| Patrol::new(&self.tiles, guard))
+    }
+
+    pub fn guards(&self) -> &[Guard] {
+        &self.guards
+    }
+
+    /// Whether the guard at `idx` in [`Map::guards`] has been found to be walking in a loop by
+    /// [`Map::move_guards_until_off_map`].
+    pub fn looping(&self, idx: usize) -> bool {
+        self.looping[idx]
+    }
+
+    /// Positions any guard has been at so far, in row-major order.
+    pub fn visited(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.tiles.len())
+            .filter(|&idx| self.visited.iter().any(|visited| visited.contains(idx)))
+            .map(|idx| self.tiles.position(idx))
     }
 
-    fn is_off_map(&self, pos: &Position) -> bool {
-        pos.x < 0 || pos.y < 0 || pos.x >= self.width || pos.y >= self.height
+    /// Positions the guard at `idx` in [`Map::guards`] has been at so far, in row-major order.
+    pub fn visited_by(&self, idx: usize) -> impl Iterator<Item = Position> + '_ {
+        self.visited[idx].iter().map(|idx| self.tiles.position(idx))
+    }
+
+    pub fn has_visited(&self, pos: &Position) -> bool {
+        self.tiles
+            .index(*pos)
+            .is_some_and(|idx| self.visited.iter().any(|visited| visited.contains(idx)))
+    }
+
+    /// The row-major index of a position on the map.
+    ///
+    /// # Panics
+    ///
+    /// If `pos` is off the map.
+    fn index(&self, pos: &Position) -> usize {
+        self.tiles.index(*pos).expect("the position is on the map")
+    }
+
+    pub fn tiles(&self) -> &Grid<Tile> {
+        &self.tiles
+    }
+
+    pub fn width(&self) -> usize {
+        self.tiles.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.height()
+    }
+
+    pub fn is_obstruction(&self, pos: &Position) -> bool {
+        self.tiles.get(*pos) == Some(&Tile::Obstruction)
+    }
+
+    pub fn is_off_map(&self, pos: &Position) -> bool {
+        !self.tiles.contains(*pos)
     }
 }
 
-#[derive(Debug, Default, Clone, Copy)]
-struct Guard {
-    position: Position,
-    heading: Heading,
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Tile {
+    #[default]
+    Open,
+    Obstruction,
 }
 
-impl Guard {
-    fn new(position: Position, heading: Heading) -> Self {
-        Self { position, heading }
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tile::Open => write!(f, "."),
+            Tile::Obstruction => write!(f, "#"),
+        }
+    }
+}
+
+/// The guard walking the map. Each step either turns the guard or moves it forward, and the
+/// patrol ends when the guard steps off the map.
+#[derive(Debug, Clone, Copy)]
+pub struct Patrol<'a> {
+    tiles: &'a Grid<Tile>,
+    pub guard: Guard,
+}
+
+impl<'a> Patrol<'a> {
+    pub fn new(tiles: &'a Grid<Tile>, guard: Guard) -> Self {
+        Self { tiles, guard }
+    }
+}
+
+// patrols are only ever compared on the same map, so only the guard matters
+impl PartialEq for Patrol<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.guard == other.guard
+    }
+}
+
+impl Eq for Patrol<'_> {}
+
+impl Hash for Patrol<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.guard.hash(state);
     }
+}
 
-    fn next_pos(&self) -> Position {
-        match self.heading {
-            Heading::North => Position {
-                x: self.position.x,
-                y: self.position.y - 1,
-            },
-            Heading::East => Position {
-                x: self.position.x + 1,
-                y: self.position.y,
-            },
-            Heading::South => Position {
-                x: self.position.x,
-                y: self.position.y + 1,
-            },
-            Heading::West => Position {
-                x: self.position.x - 1,
-                y: self.position.y,
-            },
+impl State for Patrol<'_> {
+    fn step(&self) -> Option<Self> {
+        let mut guard = self.guard;
+        let next_pos = guard.next_pos();
+        if self.tiles.get(next_pos) == Some(&Tile::Obstruction) {
+            guard.turn_right();
+        } else if self.tiles.contains(next_pos) {
+            guard.move_to(&next_pos);
+        } else {
+            return None;
         }
+        Some(Self { guard, ..*self })
     }
+}
 
-    fn turn_right(&mut self) {
-        self.heading.turn_right();
+/// The obstructions in each row and column in order, to find the next one in the guard's way
+/// without walking up to it.
+#[derive(Debug, Clone)]
+pub struct Obstructions {
+    /// The `x` of each obstruction in each row.
+    rows: Vec<Vec<isize>>,
+    /// The `y` of each obstruction in each column.
+    columns: Vec<Vec<isize>>,
+}
+
+impl Obstructions {
+    pub fn new(tiles: &Grid<Tile>) -> Self {
+        let mut rows = vec![Vec::new(); tiles.height()];
+        let mut columns = vec![Vec::new(); tiles.width()];
+        // row-major order keeps both sorted
+        for (pos, tile) in tiles.iter() {
+            if *tile == Tile::Obstruction {
+                rows[pos.y as usize].push(pos.x);
+                columns[pos.x as usize].push(pos.y);
+            }
+        }
+        Self { rows, columns }
     }
 
-    fn move_to(&mut self, next_pos: &Position) {
-        self.position = *next_pos;
+    /// The nearest obstruction in front of `guard`, if it's on the map.
+    pub fn ahead(&self, guard: &Guard) -> Option<Position> {
+        let Position { x, y } = guard.position;
+        match guard.heading {
+            Heading::North => before(self.columns.get(x as usize)?, y).map(|y| Position::new(x, y)),
+            Heading::South => after(self.columns.get(x as usize)?, y).map(|y| Position::new(x, y)),
+            Heading::West => before(self.rows.get(y as usize)?, x).map(|x| Position::new(x, y)),
+            Heading::East => after(self.rows.get(y as usize)?, x).map(|x| Position::new(x, y)),
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
-struct Position {
-    x: isize,
-    y: isize,
+/// The last of the sorted `values` less than `value`.
+fn before(values: &[isize], value: isize) -> Option<isize> {
+    let idx = values.partition_point(|&v| v < value);
+    idx.checked_sub(1).map(|idx| values[idx])
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
-enum Heading {
-    #[default]
-    North,
-    East,
-    South,
-    West,
+/// The first of the sorted `values` more than `value`.
+fn after(values: &[isize], value: isize) -> Option<isize> {
+    let idx = values.partition_point(|&v| v <= value);
+    values.get(idx).copied()
 }
 
-impl Heading {
-    fn turn_right(&mut self) -> &mut Self {
-        match self {
-            Heading::North => *self = Heading::East,
-            Heading::East => *self = Heading::South,
-            Heading::South => *self = Heading::West,
-            Heading::West => *self = Heading::North,
+/// A patrol with one extra obstruction that only stops where the guard turns, jumping straight
+/// to the next obstruction in the way.
+#[derive(Debug, Clone, Copy)]
+pub struct Jumps<'a> {
+    obstructions: &'a Obstructions,
+    extra: Position,
+    pub guard: Guard,
+}
+
+impl<'a> Jumps<'a> {
+    pub fn new(obstructions: &'a Obstructions, extra: Position, guard: Guard) -> Self {
+        Self {
+            obstructions,
+            extra,
+            guard,
         }
-        self
+    }
+
+    /// Whether the extra obstruction is in front of the guard.
+    fn extra_ahead(&self) -> bool {
+        let (dx, dy) = self.guard.heading.offset();
+        let Position { x, y } = self.guard.position;
+        let along = (self.extra.x - x) * dx + (self.extra.y - y) * dy;
+        let across = (self.extra.x - x) * dy - (self.extra.y - y) * dx;
+        along > 0 && across == 0
+    }
+}
+
+// like patrols, only the guard matters
+impl PartialEq for Jumps<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.guard == other.guard
+    }
+}
+
+impl Eq for Jumps<'_> {}
+
+impl State for Jumps<'_> {
+    fn step(&self) -> Option<Self> {
+        let distance = |pos: &Position| {
+            (pos.x - self.guard.position.x).abs() + (pos.y - self.guard.position.y).abs()
+        };
+        let obstruction = self
+            .obstructions
+            .ahead(&self.guard)
+            .into_iter()
+            .chain(self.extra_ahead().then_some(self.extra))
+            .min_by_key(distance)?;
+
+        let mut guard = self.guard;
+        guard.move_to(&(obstruction + self.guard.heading.reverse()));
+        guard.turn_right();
+        Some(Self { guard, ..*self })
+    }
+}
+
+/// A guard's state at one step of its patrol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub position: Position,
+    pub heading: Heading,
+    /// Whether the guard turned to get here, rather than moved.
+    pub turned: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Guard {
+    pub position: Position,
+    pub heading: Heading,
+}
+
+impl Guard {
+    pub fn new(position: Position, heading: Heading) -> Self {
+        Self { position, heading }
+    }
+
+    pub fn next_pos(&self) -> Position {
+        self.position + self.heading
+    }
+
+    pub fn turn_right(&mut self) {
+        self.heading = self.heading.turn_right();
+    }
+
+    pub fn move_to(&mut self, next_pos: &Position) {
+        self.position = *next_pos;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::report::TableFormat;
+    use test_case::test_case;
 
     const INPUT: &str = r#"....#.....
 .........#
@@ -232,15 +596,232 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let expected = 41;
-        let actual = Map::from_input(INPUT).num_guard_visited_positions();
+        let expected = Answer::Usize(41);
+        let actual = Map::parse(INPUT, Mode::Strict).unwrap().part1().unwrap();
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn visited() {
+        let mut map = Map::from_input(INPUT).unwrap();
+        assert_eq!(vec![Position::new(4, 6)], map.visited().collect::<Vec<_>>());
+        map.move_guards_until_off_map();
+        assert_eq!(41, map.visited().count());
+        assert!(!map.looping(0));
+        assert!(map.has_visited(&Position::new(4, 1)));
+        assert!(map.has_visited(&Position::new(7, 9)));
+        assert!(!map.has_visited(&Position::new(0, 0)));
+        assert!(!map.has_visited(&Position::new(-1, 0)));
+    }
+
+    #[test]
+    fn tiles_round_trip() {
+        let map = Map::from_input(INPUT).unwrap();
+        assert_eq!(INPUT.replace('^', "."), map.tiles().to_string());
+        assert_eq!(
+            &[Guard::new(Position::new(4, 6), Heading::North)],
+            map.guards()
+        );
+    }
+
+    #[test_case("\r\n" ; "crlf")]
+    #[test_case("  \n" ; "trailing spaces")]
+    #[test_case("\n" ; "lf")]
+    fn line_endings(ending: &str) {
+        let input = INPUT.replace('\n', ending);
+        let map = Map::from_input(&input).unwrap();
+        assert_eq!((10, 10), (map.width(), map.height()));
+        assert_eq!(Answer::Usize(41), map.part1().unwrap());
+
+        let map = Map::from_input(input.trim_end()).unwrap();
+        assert_eq!((10, 10), (map.width(), map.height()));
+    }
+
+    #[test_case("..#\n.^\n...\n", 2, 3 ; "short row")]
+    #[test_case("..#\n.^..\n...\n", 2, 4 ; "long row")]
+    #[test_case("..#\n\n.^.\n", 2, 1 ; "blank row")]
+    #[test_case("\n..#\n.^.\n", 1, 1 ; "leading blank row")]
+    fn ragged_map(input: &str, line: usize, column: usize) {
+        let err = Map::from_input(input).unwrap_err();
+        assert_eq!((line, column), (err.line, err.column), "{err}");
+    }
+
+    #[test]
+    fn guard_glyphs() {
+        let map = Map::from_input(".>.\n<.v\n").unwrap();
+        let expected = [
+            (1, 0, Heading::East),
+            (0, 1, Heading::West),
+            (2, 1, Heading::South),
+        ]
+        .map(|(x, y, heading)| Guard::new(Position::new(x, y), heading));
+        assert_eq!(&expected, map.guards());
+    }
+
+    #[test]
+    fn no_guard() {
+        let err = Map::from_input("..#\n...\n").unwrap_err();
+        assert_eq!((3, 1), (err.line, err.column));
+        assert_eq!("expected a guard, one of `^`, `>`, `v` or `<`", err.message);
+    }
+
+    #[test]
+    fn several_guards() {
+        // the first guard goes round the obstructions forever, and the second walks out
+        let mut map = Map::from_input(".#...\n....#\n.^...\n#....\n...#<\n").unwrap();
+        map.move_guards_until_off_map();
+        assert!(map.looping(0));
+        assert!(!map.looping(1));
+        assert_eq!(8, map.visited_by(0).count());
+        assert_eq!(3, map.visited_by(1).count());
+        assert_eq!(11, map.visited().count());
+        assert!(map.has_visited(&Position::new(4, 2)));
+    }
+
+    #[test]
+    fn loop_obstacles() {
+        let map = Map::from_input(INPUT).unwrap();
+        let expected = [(3, 6), (6, 7), (7, 7), (1, 8), (3, 8), (7, 9)]
+            .map(|(x, y)| Position::new(x, y))
+            .to_vec();
+        assert_eq!(expected, map.loop_obstacles());
+    }
+
+    #[test]
+    fn obstructions_ahead() {
+        let map = Map::from_input(INPUT).unwrap();
+        let obstructions = Obstructions::new(map.tiles());
+        let ahead = |x, y, heading| obstructions.ahead(&Guard::new(Position::new(x, y), heading));
+        assert_eq!(Some(Position::new(4, 0)), ahead(4, 6, Heading::North));
+        assert_eq!(Some(Position::new(9, 1)), ahead(4, 1, Heading::East));
+        assert_eq!(Some(Position::new(1, 6)), ahead(4, 6, Heading::West));
+        assert_eq!(None, ahead(4, 6, Heading::South));
+        assert_eq!(None, ahead(4, 0, Heading::North));
+    }
+
+    /// Maps of `size` by `size` made by a simple generator, with obstructions at about
+    /// `1 / density` of the cells and a guard in the middle. A second guard starts in the bottom
+    /// left corner if `two_guards` is set.
+    fn generated_maps(size: usize, density: u64, two_guards: bool) -> impl Iterator<Item = Map> {
+        (1..40u64).map(move |seed| {
+            let mut state = seed;
+            let mut input = String::new();
+            for y in 0..size {
+                for x in 0..size {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    let glyph = if (x, y) == (size / 2, size / 2) {
+                        '^'
+                    } else if two_guards && (x, y) == (0, size - 1) {
+                        '>'
+                    } else if (state >> 33) % density == 0 {
+                        '#'
+                    } else {
+                        '.'
+                    };
+                    input.push(glyph);
+                }
+                input.push('\n');
+            }
+            Map::from_input(&input).unwrap()
+        })
+    }
+
+    #[test_case(false ; "one guard")]
+    #[test_case(true ; "two guards")]
+    fn loop_obstacles_agree_with_brute_force(two_guards: bool) {
+        for map in generated_maps(12, 6, two_guards) {
+            let leaving: Vec<Guard> = map
+                .patrols()
+                .filter(|&patrol| !simulation::brent(patrol).is_loop())
+                .map(|patrol| patrol.guard)
+                .collect();
+            let brute_force: Vec<Position> = map
+                .tiles()
+                .positions()
+                .filter(|pos| {
+                    !map.is_obstruction(pos) && map.guards().iter().all(|g| g.position != *pos)
+                })
+                .filter(|&pos| {
+                    let mut tiles = map.tiles().clone();
+                    tiles.set(pos, Tile::Obstruction);
+                    leaving
+                        .iter()
+                        .any(|&guard| simulation::brent(Patrol::new(&tiles, guard)).is_loop())
+                })
+                .collect();
+            assert_eq!(brute_force, map.loop_obstacles(), "\n{}", map.tiles());
+        }
+    }
+
+    #[test]
+    fn visualize() {
+        let map = Map::from_input(INPUT).unwrap();
+        let drawn = visualize::ansi(&Solution::visualize(&map, Part::Two).unwrap());
+        assert_eq!(6, drawn.matches('O').count());
+        assert_eq!(1, drawn.matches("\x1b[1;32m^").count());
+
+        let frames = Solution::animate(&map).unwrap();
+        // the starting position, then one frame for every move and turn
+        assert_eq!(55, frames.count());
+
+        // the second guard walks out long before the first comes round again
+        let map = Map::from_input(".#...\n....#\n.^...\n#....\n...#<\n").unwrap();
+        let frames: Vec<Picture> = Solution::animate(&map).unwrap().collect();
+        let guards = |frame: &Picture| {
+            frame
+                .iter()
+                .filter(|(_, (_, style))| *style == Style::Actor)
+                .count()
+        };
+        let [first, second] = [0, 1].map(|idx| map.walk(map.guards()[idx]).count());
+        assert!(second < first);
+        assert_eq!(first, frames.len());
+        assert!(frames.iter().all(|frame| guards(frame) == 2));
+    }
+
+    #[test]
+    fn steps() {
+        let map = Map::from_input(INPUT).unwrap();
+        let steps: Vec<Step> = map.steps(map.guards()[0]).collect();
+        assert_eq!(55, steps.len());
+        assert_eq!(10, steps.iter().filter(|step| step.turned).count());
+        let step = |x, y, heading, turned| Step {
+            position: Position::new(x, y),
+            heading,
+            turned,
+        };
+        assert_eq!(step(4, 6, Heading::North, false), steps[0]);
+        assert_eq!(step(4, 1, Heading::North, false), steps[5]);
+        assert_eq!(step(4, 1, Heading::East, true), steps[6]);
+        assert_eq!(step(5, 1, Heading::East, false), steps[7]);
+        assert_eq!(step(7, 9, Heading::South, false), steps[54]);
+    }
+
+    #[test]
+    fn trace() {
+        let map = Map::from_input(INPUT).unwrap();
+        let [path, obstacles] = <[Table; 2]>::try_from(Solution::trace(&map).unwrap()).unwrap();
+        let path = path.render(TableFormat::Csv);
+        let mut lines = path.lines();
+        assert_eq!(Some("guard,step,x,y,heading,turned"), lines.next());
+        assert_eq!(Some("0,0,4,6,North,false"), lines.next());
+        assert_eq!(Some("0,6,4,1,East,true"), lines.nth(5));
+        assert_eq!(55, path.lines().count() - 1);
+
+        let obstacles = obstacles.render(TableFormat::Json);
+        assert_eq!(6, obstacles.matches('{').count());
+        assert!(
+            obstacles.starts_with("[\n  {\"x\":3,\"y\":6},\n"),
+            "{obstacles}"
+        );
+    }
+
     #[test]
     fn part_2() {
-        let expected = 6;
-        let actual = Map::from_input(INPUT).num_new_obstacles_for_loop();
+        let expected = Answer::Usize(6);
+        let actual = Map::parse(INPUT, Mode::Strict).unwrap().part2().unwrap();
         assert_eq!(expected, actual);
     }
 }
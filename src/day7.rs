@@ -1,46 +1,70 @@
 use itertools::Itertools;
 use rayon::prelude::*;
 
-pub fn main() {
-    const INPUT: &str = include_str!("../inputs/7");
-    println!(
-        "day 7 part 1: {}",
-        total_calibration_result(INPUT, OPS_PART_1)
-    );
-    println!(
-        "day 7 part 2: {}",
-        total_calibration_result(INPUT, OPS_PART_2)
-    );
+use crate::{
+    answer::{Answer, Total},
+    parse::{self, Mode, ParseError},
+    solution::{Solution, SolveError},
+};
+
+pub struct Calibration {
+    pub equations: Vec<Equation>,
+}
+
+impl Solution for Calibration {
+    const DAY: u8 = 7;
+
+    fn parse(input: &str, mode: Mode) -> Result<Self, ParseError> {
+        Ok(Self {
+            equations: parse_input(input, mode)?,
+        })
+    }
+
+    fn part1(&self) -> Result<Answer, SolveError> {
+        Ok(total_calibration_result(&self.equations, OPS_PART_1).into())
+    }
+
+    fn part2(&self) -> Result<Answer, SolveError> {
+        Ok(total_calibration_result(&self.equations, OPS_PART_2).into())
+    }
 }
 
-struct Equation {
-    nums: Vec<usize>,
-    result: usize,
+#[derive(Debug)]
+pub struct Equation {
+    pub nums: Vec<usize>,
+    pub result: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Operand {
+pub enum Operand {
     Add,
     Multiply,
     Concatenate,
 }
 
-const OPS_PART_1: [Operand; 2] = [Operand::Add, Operand::Multiply];
-const OPS_PART_2: [Operand; 3] = [Operand::Add, Operand::Multiply, Operand::Concatenate];
+pub const OPS_PART_1: [Operand; 2] = [Operand::Add, Operand::Multiply];
+pub const OPS_PART_2: [Operand; 3] = [Operand::Add, Operand::Multiply, Operand::Concatenate];
 
 impl Operand {
-    fn apply(&self, a: usize, b: usize) -> usize {
+    /// `None` if the result would overflow.
+    pub fn apply(&self, a: usize, b: usize) -> Option<usize> {
         match self {
-            Operand::Add => a + b,
-            Operand::Multiply => a * b,
-            Operand::Concatenate => format!("{a}{b}").parse().unwrap_or_default(),
+            Operand::Add => a.checked_add(b),
+            Operand::Multiply => a.checked_mul(b),
+            Operand::Concatenate => {
+                let digits = b.checked_ilog10().unwrap_or(0) + 1;
+                a.checked_mul(10usize.checked_pow(digits)?)?.checked_add(b)
+            }
         }
     }
 }
 
 impl Equation {
-    fn has_solution<const N: usize>(&self, ops: [Operand; N]) -> bool {
-        let num_operands = self.nums.len() - 1;
+    /// Always false with no numbers.
+    pub fn has_solution<const N: usize>(&self, ops: [Operand; N]) -> bool {
+        let Some(num_operands) = self.nums.len().checked_sub(1) else {
+            return false;
+        };
         ops.iter()
             .cycle()
             .take(N * num_operands)
@@ -49,49 +73,49 @@ impl Equation {
             .unique()
             .par_bridge()
             .map(|operands| self.apply_operands(&operands))
-            .find_any(|result| *result == self.result)
+            .find_any(|result| *result == Some(self.result))
             .is_some()
     }
 
-    fn apply_operands(&self, operands: &[Operand]) -> usize {
-        let mut operands = operands.iter();
-
-        self.nums
-            .iter()
-            .copied()
-            .reduce(|a, b| {
-                if a > self.result {
-                    return a;
-                }
-                let Some(op) = operands.next() else {
-                    return a;
-                };
-                op.apply(a, b)
-            })
-            .unwrap_or_default()
+    /// `None` if there are no numbers or an intermediate result overflows, which can't be the
+    /// answer as it's more than any `result`.
+    pub fn apply_operands(&self, operands: &[Operand]) -> Option<usize> {
+        let (&first, rest) = self.nums.split_first()?;
+        rest.iter().zip(operands).try_fold(first, |a, (&b, op)| {
+            if a > self.result {
+                return Some(a);
+            }
+            op.apply(a, b)
+        })
     }
 }
 
-fn total_calibration_result<const N: usize>(input: &str, ops: [Operand; N]) -> usize {
-    input
-        .par_lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .filter_map(|line| line.split_once(": "))
-        .filter_map(|(res, nums)| {
-            let result = res.parse().ok()?;
-            let nums = nums.split(' ').filter_map(|num| num.parse().ok()).collect();
-
-            Some(Equation { nums, result })
-        })
+pub fn total_calibration_result<const N: usize>(
+    equations: &[Equation],
+    ops: [Operand; N],
+) -> Total {
+    equations
+        .par_iter()
         .filter(|eq| eq.has_solution(ops))
-        .map(|eq| eq.result)
+        .map(|eq| eq.result as u128)
         .sum()
 }
 
+pub fn parse_input(input: &str, mode: Mode) -> Result<Vec<Equation>, ParseError> {
+    parse::lines(
+        input,
+        mode,
+        parse::complete(|line| {
+            let (rest, (result, nums)) = parse::key_values(line)?;
+            Ok((rest, Equation { nums, result }))
+        }),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_case::test_case;
 
     const INPUT: &str = r#"190: 10 19
 3267: 81 40 27
@@ -106,15 +130,68 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let expected = 3749;
-        let actual = total_calibration_result(INPUT, OPS_PART_1);
+        let expected = Answer::Usize(3749);
+        let actual = Calibration::parse(INPUT, Mode::Strict)
+            .unwrap()
+            .part1()
+            .unwrap();
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn part_2() {
-        let expected = 11387;
-        let actual = total_calibration_result(INPUT, OPS_PART_2);
+        let expected = Answer::Usize(11387);
+        let actual = Calibration::parse(INPUT, Mode::Strict)
+            .unwrap()
+            .part2()
+            .unwrap();
         assert_eq!(expected, actual)
     }
+
+    #[test_case(Operand::Add, 7, 35, Some(42))]
+    #[test_case(Operand::Multiply, 6, 7, Some(42))]
+    #[test_case(Operand::Concatenate, 4, 2, Some(42))]
+    #[test_case(Operand::Concatenate, 4, 0, Some(40))]
+    #[test_case(Operand::Concatenate, 1, 10, Some(110))]
+    #[test_case(Operand::Add, usize::MAX, 1, None)]
+    #[test_case(Operand::Multiply, usize::MAX / 2, 3, None)]
+    #[test_case(Operand::Concatenate, usize::MAX / 10, 99, None)]
+    fn apply(op: Operand, a: usize, b: usize, expected: Option<usize>) {
+        assert_eq!(expected, op.apply(a, b));
+    }
+
+    #[test]
+    fn overflowing_operands_never_match() {
+        // 2 * (2^63 + 1) wraps round to 2
+        let input = format!("2: 2 {}\n", (1usize << 63) + 1);
+        let calibration = Calibration::parse(&input, Mode::Strict).unwrap();
+        assert_eq!(Answer::Usize(0), calibration.part1().unwrap());
+
+        // the sum of the results overflows a `usize`
+        let max = usize::MAX;
+        let input = format!("{max}: {max} 0\n{max}: {max} 0\n");
+        let calibration = Calibration::parse(&input, Mode::Strict).unwrap();
+        assert_eq!(Answer::U128(2 * max as u128), calibration.part1().unwrap());
+    }
+
+    #[test]
+    fn no_numbers() {
+        let equation = Equation {
+            result: 0,
+            nums: vec![],
+        };
+        assert!(!equation.has_solution([Operand::Add, Operand::Multiply]));
+    }
+
+    #[test]
+    fn strict_rejects_malformed_line() {
+        let err = parse_input("190: 10 19\n3267 81 40 27\n", Mode::Strict).unwrap_err();
+        assert_eq!((2, 1), (err.line, err.column));
+        assert_eq!(
+            1,
+            parse_input("190: 10 19\n3267 81 40 27\n", Mode::Lenient)
+                .unwrap()
+                .len()
+        );
+    }
 }
@@ -0,0 +1,246 @@
+use std::ops::Add;
+
+use crate::grid::Position;
+
+/// One of the 4 orthogonal compass directions. North is up, towards y-1.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Direction4 {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction4 {
+    /// Every direction, clockwise from north.
+    pub const ALL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::ALL.into_iter()
+    }
+
+    pub const fn turn_right(self) -> Self {
+        match self {
+            Self::North => Self::East,
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+        }
+    }
+
+    pub const fn turn_left(self) -> Self {
+        match self {
+            Self::North => Self::West,
+            Self::East => Self::North,
+            Self::South => Self::East,
+            Self::West => Self::South,
+        }
+    }
+
+    pub const fn reverse(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::East => Self::West,
+            Self::South => Self::North,
+            Self::West => Self::East,
+        }
+    }
+
+    /// The `(dx, dy)` of one step in this direction.
+    pub const fn offset(self) -> (isize, isize) {
+        match self {
+            Self::North => (0, -1),
+            Self::East => (1, 0),
+            Self::South => (0, 1),
+            Self::West => (-1, 0),
+        }
+    }
+
+    /// Parses an arrow glyph: `^`, `>`, `v` or `<`.
+    pub const fn from_glyph(glyph: char) -> Option<Self> {
+        match glyph {
+            '^' => Some(Self::North),
+            '>' => Some(Self::East),
+            'v' => Some(Self::South),
+            '<' => Some(Self::West),
+            _ => None,
+        }
+    }
+
+    pub const fn glyph(self) -> char {
+        match self {
+            Self::North => '^',
+            Self::East => '>',
+            Self::South => 'v',
+            Self::West => '<',
+        }
+    }
+}
+
+/// One of the 8 compass directions, including the diagonals.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Direction8 {
+    #[default]
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    /// Every direction, clockwise from north.
+    pub const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::ALL.into_iter()
+    }
+
+    /// Turns 45 degrees clockwise.
+    pub const fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    /// Turns 45 degrees anticlockwise.
+    pub const fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    pub const fn reverse(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    /// The `(dx, dy)` of one step in this direction.
+    pub const fn offset(self) -> (isize, isize) {
+        match self {
+            Self::North => (0, -1),
+            Self::NorthEast => (1, -1),
+            Self::East => (1, 0),
+            Self::SouthEast => (1, 1),
+            Self::South => (0, 1),
+            Self::SouthWest => (-1, 1),
+            Self::West => (-1, 0),
+            Self::NorthWest => (-1, -1),
+        }
+    }
+
+    /// Parses an arrow glyph: `^`, `>`, `v` or `<`. Diagonals have no glyph.
+    pub const fn from_glyph(glyph: char) -> Option<Self> {
+        match Direction4::from_glyph(glyph) {
+            Some(direction) => Some(Self::from_4(direction)),
+            None => None,
+        }
+    }
+
+    const fn from_4(direction: Direction4) -> Self {
+        match direction {
+            Direction4::North => Self::North,
+            Direction4::East => Self::East,
+            Direction4::South => Self::South,
+            Direction4::West => Self::West,
+        }
+    }
+}
+
+impl From<Direction4> for Direction8 {
+    fn from(direction: Direction4) -> Self {
+        Self::from_4(direction)
+    }
+}
+
+impl Add<Direction4> for Position {
+    type Output = Self;
+
+    /// One step from this position in `direction`.
+    fn add(self, direction: Direction4) -> Self::Output {
+        let (dx, dy) = direction.offset();
+        self.offset(dx, dy)
+    }
+}
+
+impl Add<Direction8> for Position {
+    type Output = Self;
+
+    /// One step from this position in `direction`.
+    fn add(self, direction: Direction8) -> Self::Output {
+        let (dx, dy) = direction.offset();
+        self.offset(dx, dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn rotations_4() {
+        for direction in Direction4::all() {
+            assert_eq!(direction, direction.turn_right().turn_left());
+            assert_eq!(direction.reverse(), direction.turn_right().turn_right());
+            let (dx, dy) = direction.offset();
+            assert_eq!((-dx, -dy), direction.reverse().offset());
+        }
+        assert_eq!(Direction4::East, Direction4::North.turn_right());
+        assert_eq!(Direction4::West, Direction4::North.turn_left());
+    }
+
+    #[test]
+    fn rotations_8() {
+        for direction in Direction8::all() {
+            assert_eq!(direction, direction.turn_right().turn_left());
+            assert_eq!(
+                direction.reverse(),
+                direction
+                    .turn_right()
+                    .turn_right()
+                    .turn_right()
+                    .turn_right()
+            );
+            let (dx, dy) = direction.offset();
+            assert_eq!((-dx, -dy), direction.reverse().offset());
+        }
+        assert_eq!(Direction8::NorthWest, Direction8::North.turn_left());
+        assert_eq!(Direction8::SouthWest, Direction8::NorthEast.reverse());
+    }
+
+    #[test]
+    fn orthogonal_offsets_agree() {
+        for direction in Direction4::all() {
+            assert_eq!(direction.offset(), Direction8::from(direction).offset());
+        }
+    }
+
+    #[test_case('^', Some(Direction4::North))]
+    #[test_case('>', Some(Direction4::East))]
+    #[test_case('v', Some(Direction4::South))]
+    #[test_case('<', Some(Direction4::West))]
+    #[test_case('#', None)]
+    fn glyphs(glyph: char, expected: Option<Direction4>) {
+        assert_eq!(expected, Direction4::from_glyph(glyph));
+        if let Some(direction) = expected {
+            assert_eq!(glyph, direction.glyph());
+            assert_eq!(Some(direction.into()), Direction8::from_glyph(glyph));
+        }
+    }
+
+    #[test]
+    fn step() {
+        let pos = Position::new(3, 3);
+        assert_eq!(Position::new(3, 2), pos + Direction4::North);
+        assert_eq!(Position::new(2, 4), pos + Direction8::SouthWest);
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::Hash,
+};
+
+/// A directed graph stored as adjacency lists.
+///
+/// Nodes and edges are kept in the order they were added, so every traversal is deterministic.
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    successors: Vec<Vec<N>>,
+}
+
+/// A cycle of nodes, each with an edge to the next and the last with an edge back to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<N>(pub Vec<N>);
+
+impl<N: fmt::Display> fmt::Display for Cycle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node in &self.0 {
+            write!(f, "{node} -> ")?;
+        }
+        match self.0.first() {
+            Some(first) => write!(f, "{first}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<N> Default for Graph<N> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            successors: Vec::new(),
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash> Graph<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        if !self.index.contains_key(&node) {
+            self.index.insert(node, self.nodes.len());
+            self.nodes.push(node);
+            self.successors.push(Vec::new());
+        }
+    }
+
+    /// Adds an edge from `from` to `to`, adding either node if it's new. Duplicate edges are
+    /// ignored.
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.add_node(from);
+        self.add_node(to);
+        let successors = &mut self.successors[self.index[&from]];
+        if !successors.contains(&to) {
+            successors.push(to);
+        }
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn contains(&self, node: N) -> bool {
+        self.index.contains_key(&node)
+    }
+
+    /// The nodes `node` has an edge to, empty if `node` isn't in the graph.
+    pub fn successors(&self, node: N) -> &[N] {
+        self.index
+            .get(&node)
+            .map_or(&[], |&idx| self.successors[idx].as_slice())
+    }
+
+    pub fn has_edge(&self, from: N, to: N) -> bool {
+        self.successors(from).contains(&to)
+    }
+
+    /// Orders `subset` so that every edge between two of its nodes points forwards, ignoring
+    /// edges to or from nodes outside it. Ties keep the order of `subset`.
+    ///
+    /// Fails with one of the cycles if there's no such order.
+    pub fn topo_sort(&self, subset: &[N]) -> Result<Vec<N>, Cycle<N>> {
+        let members: HashSet<N> = subset.iter().copied().collect();
+        let mut seen = HashSet::new();
+        let nodes: Vec<N> = subset
+            .iter()
+            .copied()
+            .filter(|node| seen.insert(*node))
+            .collect();
+
+        let mut in_degree: HashMap<N, usize> = nodes.iter().map(|&node| (node, 0)).collect();
+        for &node in &nodes {
+            for to in self.successors(node) {
+                if let Some(degree) = in_degree.get_mut(to) {
+                    *degree += 1;
+                }
+            }
+        }
+
+        let mut ready: VecDeque<N> = nodes
+            .iter()
+            .copied()
+            .filter(|node| in_degree[node] == 0)
+            .collect();
+        let mut sorted = Vec::with_capacity(nodes.len());
+        while let Some(node) = ready.pop_front() {
+            sorted.push(node);
+            for to in self.successors(node) {
+                if let Some(degree) = in_degree.get_mut(to) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.push_back(*to);
+                    }
+                }
+            }
+        }
+
+        if sorted.len() == nodes.len() {
+            return Ok(sorted);
+        }
+        let cycle = self
+            .cycle_among(&nodes, |node| members.contains(&node))
+            .expect("nodes left unsorted are on a cycle");
+        Err(cycle)
+    }
+
+    /// Any cycle in the graph.
+    pub fn find_cycle(&self) -> Option<Cycle<N>> {
+        self.cycle_among(&self.nodes, |_| true)
+    }
+
+    /// A depth-first search from each of `roots` in turn that only follows edges to nodes
+    /// accepted by `member`.
+    fn cycle_among(&self, roots: &[N], member: impl Fn(N) -> bool) -> Option<Cycle<N>> {
+        #[derive(PartialEq, Eq)]
+        enum Mark {
+            OnPath,
+            Done,
+        }
+
+        let mut marks = HashMap::new();
+        for &root in roots {
+            if marks.contains_key(&root) {
+                continue;
+            }
+            marks.insert(root, Mark::OnPath);
+            // each node on the path with the index of the next successor to visit
+            let mut path = vec![(root, 0)];
+            while let Some(&(node, next)) = path.last() {
+                let Some(&to) = self.successors(node).get(next) else {
+                    marks.insert(node, Mark::Done);
+                    path.pop();
+                    continue;
+                };
+                if let Some((_, next)) = path.last_mut() {
+                    *next += 1;
+                }
+                if !member(to) {
+                    continue;
+                }
+                match marks.get(&to) {
+                    None => {
+                        marks.insert(to, Mark::OnPath);
+                        path.push((to, 0));
+                    }
+                    Some(Mark::OnPath) => {
+                        let start = path.iter().position(|&(node, _)| node == to)?;
+                        return Some(Cycle(path[start..].iter().map(|&(node, _)| node).collect()));
+                    }
+                    Some(Mark::Done) => {}
+                }
+            }
+        }
+        None
+    }
+
+    /// A graph with an edge from each node to every node reachable from it. A node only has an
+    /// edge to itself if it's on a cycle.
+    pub fn transitive_closure(&self) -> Self {
+        let mut closure = Self::new();
+        for &from in &self.nodes {
+            closure.add_node(from);
+            let mut stack = self.successors(from).to_vec();
+            let mut reached = HashSet::new();
+            while let Some(node) = stack.pop() {
+                if reached.insert(node) {
+                    closure.add_edge(from, node);
+                    stack.extend_from_slice(self.successors(node));
+                }
+            }
+        }
+        closure
+    }
+}
+
+impl<N: Copy + Eq + Hash> FromIterator<(N, N)> for Graph<N> {
+    fn from_iter<T: IntoIterator<Item = (N, N)>>(edges: T) -> Self {
+        let mut graph = Self::new();
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(u32, u32)]) -> Graph<u32> {
+        edges.iter().copied().collect()
+    }
+
+    #[test]
+    fn edges() {
+        let graph = graph(&[(1, 2), (1, 3), (1, 2), (3, 4)]);
+        assert_eq!(&[1, 2, 3, 4], graph.nodes());
+        assert_eq!(&[2, 3], graph.successors(1));
+        assert!(graph.has_edge(3, 4));
+        assert!(!graph.has_edge(4, 3));
+        assert!(graph.successors(9).is_empty());
+    }
+
+    #[test]
+    fn topo_sort_subset() {
+        // 5 -> 1 -> 2 -> 3, with 4 -> 1 outside the subset
+        let graph = graph(&[(1, 2), (2, 3), (5, 1), (4, 1), (3, 4)]);
+        assert_eq!(Ok(vec![5, 1, 2, 3]), graph.topo_sort(&[3, 2, 5, 1]));
+        // only the edges within the subset count, so 3 -> 4 -> 1 isn't a cycle here
+        assert_eq!(Ok(vec![1, 3]), graph.topo_sort(&[1, 3]));
+        assert_eq!(Ok(vec![3, 1]), graph.topo_sort(&[3, 1]));
+    }
+
+    #[test]
+    fn topo_sort_reports_cycle() {
+        let graph = graph(&[(1, 2), (2, 3), (3, 1), (3, 4)]);
+        assert_eq!(Err(Cycle(vec![3, 1, 2])), graph.topo_sort(&[4, 3, 2, 1]));
+        assert_eq!(Ok(vec![2, 3, 4]), graph.topo_sort(&[4, 3, 2]));
+    }
+
+    #[test]
+    fn find_cycle() {
+        assert_eq!(None, graph(&[(1, 2), (1, 3), (2, 3)]).find_cycle());
+
+        let cycle = graph(&[(1, 2), (2, 3), (3, 4), (4, 2)])
+            .find_cycle()
+            .unwrap();
+        assert_eq!(Cycle(vec![2, 3, 4]), cycle);
+        assert_eq!("2 -> 3 -> 4 -> 2", cycle.to_string());
+
+        assert_eq!(Some(Cycle(vec![7])), graph(&[(7, 7)]).find_cycle());
+    }
+
+    #[test]
+    fn transitive_closure() {
+        let closure = graph(&[(1, 2), (2, 3), (4, 4)]).transitive_closure();
+        assert_eq!(&[2, 3], closure.successors(1));
+        assert_eq!(&[3], closure.successors(2));
+        assert!(closure.successors(3).is_empty());
+        assert_eq!(&[4], closure.successors(4));
+    }
+}
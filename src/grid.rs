@@ -0,0 +1,293 @@
+use std::{fmt, ops::Add};
+
+use crate::direction::{Direction4, Direction8};
+
+/// A cell position. Top left is 0,0, `x` grows to the right and `y` grows downwards.
+///
+/// Coordinates are signed so that positions just off the grid can be represented.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Position {
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    pub const fn offset(self, dx: isize, dy: isize) -> Self {
+        Self {
+            x: self.x + dx,
+            y: self.y + dy,
+        }
+    }
+}
+
+impl Add for Position {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.offset(rhs.x, rhs.y)
+    }
+}
+
+/// A dense, rectangular grid of cells stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// A grid of `cells` laid out row by row. See [`crate::parse::grid`] for reading one from text.
+    ///
+    /// # Panics
+    ///
+    /// If there aren't exactly `width * height` cells.
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len(), "grid size mismatch");
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.width && (pos.y as usize) < self.height
+    }
+
+    /// Index of `pos` in row-major order, if it's on the grid.
+    pub fn index(&self, pos: Position) -> Option<usize> {
+        self.contains(pos)
+            .then(|| pos.y as usize * self.width + pos.x as usize)
+    }
+
+    /// Position of the cell at row-major `idx`.
+    pub fn position(&self, idx: usize) -> Position {
+        Position::new((idx % self.width) as isize, (idx / self.width) as isize)
+    }
+
+    pub fn get(&self, pos: Position) -> Option<&T> {
+        self.index(pos).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, pos: Position) -> Option<&mut T> {
+        self.index(pos).map(|idx| &mut self.cells[idx])
+    }
+
+    /// Replaces the cell at `pos`, returning the old value, or `None` if `pos` is off the grid.
+    pub fn set(&mut self, pos: Position, value: T) -> Option<T> {
+        self.get_mut(pos).map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// Every position in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.cells.len()).map(|idx| self.position(idx))
+    }
+
+    /// Every cell with its position, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| (self.position(idx), cell))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on 0, and an empty grid has no rows anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let len = if x < self.width { self.height } else { 0 };
+        self.cells
+            .iter()
+            .skip(x)
+            .step_by(self.width.max(1))
+            .take(len)
+    }
+
+    /// Cells from `start` stepping by `(dx, dy)` until leaving the grid, starting with `start`
+    /// itself.
+    pub fn ray(
+        &self,
+        start: Position,
+        (dx, dy): (isize, isize),
+    ) -> impl Iterator<Item = (Position, &T)> {
+        std::iter::successors(Some(start), move |pos| Some(pos.offset(dx, dy)))
+            .map_while(|pos| Some((pos, self.get(pos)?)))
+    }
+
+    /// The diagonal running down and to the right from `start`.
+    pub fn diagonal(&self, start: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.ray(start, (1, 1))
+    }
+
+    /// The diagonal running down and to the left from `start`.
+    pub fn anti_diagonal(&self, start: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.ray(start, (-1, 1))
+    }
+
+    /// The orthogonal neighbours of `pos` that are on the grid, clockwise from north.
+    pub fn neighbours4(&self, pos: Position) -> impl Iterator<Item = (Position, &T)> {
+        Direction4::all().filter_map(move |direction| {
+            let neighbour = pos + direction;
+            Some((neighbour, self.get(neighbour)?))
+        })
+    }
+
+    /// The orthogonal and diagonal neighbours of `pos` that are on the grid, clockwise from
+    /// north.
+    pub fn neighbours8(&self, pos: Position) -> impl Iterator<Item = (Position, &T)> {
+        Direction8::all().filter_map(move |direction| {
+            let neighbour = pos + direction;
+            Some((neighbour, self.get(neighbour)?))
+        })
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Draws the grid as text, one line per row.
+    pub fn render(&self, mut glyph: impl FnMut(Position, &T) -> char) -> String {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for (pos, cell) in self.iter() {
+            text.push(glyph(pos, cell));
+            if pos.x as usize == self.width - 1 {
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows().filter(|row| !row.is_empty()) {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const INPUT: &str = "abc\r\ndef  \nghi\n\n";
+
+    fn grid() -> Grid<char> {
+        parse::grid(INPUT, |_, c| c).unwrap()
+    }
+
+    #[test]
+    fn display() {
+        let grid = grid();
+        assert_eq!((3, 3), (grid.width(), grid.height()));
+        assert_eq!("abc\ndef\nghi\n", grid.to_string());
+    }
+
+    #[test]
+    fn get_and_set() {
+        let mut grid = grid();
+        assert_eq!(Some(&'f'), grid.get(Position::new(2, 1)));
+        assert_eq!(None, grid.get(Position::new(3, 1)));
+        assert_eq!(None, grid.get(Position::new(-1, 0)));
+
+        assert_eq!(Some('e'), grid.set(Position::new(1, 1), 'x'));
+        assert_eq!(None, grid.set(Position::new(1, 3), 'x'));
+        assert_eq!("abc\ndxf\nghi\n", grid.to_string());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = grid();
+        assert_eq!(Some(&['d', 'e', 'f'][..]), grid.row(1));
+        assert_eq!(None, grid.row(3));
+        assert_eq!(
+            vec!['c', 'f', 'i'],
+            grid.column(2).copied().collect::<Vec<_>>()
+        );
+        assert_eq!(0, grid.column(3).count());
+        assert_eq!(3, grid.rows().count());
+    }
+
+    #[test]
+    fn diagonals() {
+        let grid = grid();
+        let cells = |iter: &mut dyn Iterator<Item = (Position, &char)>| {
+            iter.map(|(_, c)| *c).collect::<String>()
+        };
+        assert_eq!("aei", cells(&mut grid.diagonal(Position::new(0, 0))));
+        assert_eq!("bf", cells(&mut grid.diagonal(Position::new(1, 0))));
+        assert_eq!("ceg", cells(&mut grid.anti_diagonal(Position::new(2, 0))));
+        assert_eq!("ifc", cells(&mut grid.ray(Position::new(2, 2), (0, -1))));
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = grid();
+        let corner: String = grid
+            .neighbours4(Position::new(0, 0))
+            .map(|(_, c)| *c)
+            .collect();
+        assert_eq!("bd", corner);
+
+        let centre: String = grid
+            .neighbours8(Position::new(1, 1))
+            .map(|(_, c)| *c)
+            .collect();
+        assert_eq!("bcfihgda", centre);
+    }
+
+    #[test]
+    fn render() {
+        let grid = grid();
+        let text = grid.render(|pos, c| if pos.x == pos.y { '*' } else { *c });
+        assert_eq!("*bc\nd*f\ngh*\n", text);
+    }
+}
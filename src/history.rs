@@ -0,0 +1,270 @@
+use std::{fmt::Write as _, fs, io::ErrorKind, path::Path, time::Duration};
+
+use crate::{
+    bench::{Benchmark, Stage, CSV_HEADER},
+    timing,
+};
+
+/// A benchmark result recorded against the git revision it was measured at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub revision: String,
+    pub benchmark: Benchmark,
+}
+
+/// Reads the history file, which is empty if it doesn't exist yet.
+pub fn read(path: &Path) -> Result<Vec<Record>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(format!("failed to read {}: {err}", path.display())),
+    };
+    parse(&contents).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn parse(contents: &str) -> Result<Vec<Record>, String> {
+    contents
+        .lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            let (revision, benchmark) = line
+                .split_once(',')
+                .ok_or_else(|| format!("line {}: missing revision", idx + 1))?;
+            let benchmark =
+                Benchmark::from_csv(benchmark).map_err(|err| format!("line {}: {err}", idx + 1))?;
+            Ok(Record {
+                revision: revision.to_string(),
+                benchmark,
+            })
+        })
+        .collect()
+}
+
+pub fn write(path: &Path, records: &[Record]) -> Result<(), String> {
+    let mut contents = format!("revision,{CSV_HEADER}\n");
+    for record in records {
+        let _ = writeln!(
+            contents,
+            "{},{}",
+            record.revision,
+            record.benchmark.to_csv()
+        );
+    }
+    fs::write(path, contents).map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+/// Adds `benchmarks` to the history under `revision`, replacing any earlier result for the same
+/// revision, day and stage.
+pub fn record(records: &mut Vec<Record>, revision: &str, benchmarks: &[Benchmark]) {
+    records.retain(|record| {
+        record.revision != revision
+            || !benchmarks.iter().any(|bench| {
+                bench.day == record.benchmark.day && bench.stage == record.benchmark.stage
+            })
+    });
+    records.extend(benchmarks.iter().map(|benchmark| Record {
+        revision: revision.to_string(),
+        benchmark: benchmark.clone(),
+    }));
+}
+
+/// Adds `benchmarks` to the history file under `revision`. Without a revision nothing is
+/// recorded and the result is `Ok(false)`.
+pub fn update(
+    path: &Path,
+    revision: Option<&str>,
+    benchmarks: &[Benchmark],
+) -> Result<bool, String> {
+    let Some(revision) = revision else {
+        return Ok(false);
+    };
+    let mut records = read(path)?;
+    record(&mut records, revision, benchmarks);
+    write(path, &records)?;
+    Ok(true)
+}
+
+/// The short hash of the commit the binary was built from, suffixed with `-dirty` if the working
+/// tree had changes then, or `None` if it wasn't built in a git checkout. Set by `build.rs`, so
+/// checking out another revision has no effect until the next build.
+pub fn build_revision() -> Option<&'static str> {
+    option_env!("GIT_REVISION")
+}
+
+/// Revisions in the order they were first recorded.
+pub fn revisions(records: &[Record]) -> Vec<&str> {
+    let mut revisions: Vec<&str> = Vec::new();
+    for record in records {
+        if !revisions.contains(&record.revision.as_str()) {
+            revisions.push(&record.revision);
+        }
+    }
+    revisions
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub day: u8,
+    pub stage: Stage,
+    pub baseline: Duration,
+    pub current: Duration,
+    /// Relative change in median time, as a percentage of the baseline.
+    pub change: f64,
+    pub regressed: bool,
+}
+
+/// Compares the median times of every day and stage recorded at both revisions, flagging those
+/// that got slower by more than `threshold` percent.
+pub fn compare(
+    records: &[Record],
+    baseline: &str,
+    current: &str,
+    threshold: f64,
+) -> Vec<Comparison> {
+    let mut comparisons: Vec<Comparison> = at(records, current)
+        .filter_map(|current| {
+            let baseline = at(records, baseline)
+                .find(|bench| bench.day == current.day && bench.stage == current.stage)?;
+            let baseline_median = baseline.stats.median.as_secs_f64();
+            let current_median = current.stats.median.as_secs_f64();
+            let change = if baseline_median == 0.0 {
+                0.0
+            } else {
+                (current_median - baseline_median) / baseline_median * 100.0
+            };
+            Some(Comparison {
+                day: current.day,
+                stage: current.stage,
+                baseline: baseline.stats.median,
+                current: current.stats.median,
+                change,
+                regressed: change > threshold,
+            })
+        })
+        .collect();
+    comparisons.sort_by_key(|comparison| (comparison.day, comparison.stage));
+    comparisons
+}
+
+fn at<'a>(records: &'a [Record], revision: &'a str) -> impl Iterator<Item = &'a Benchmark> {
+    records
+        .iter()
+        .filter(move |record| record.revision == revision)
+        .map(|record| &record.benchmark)
+}
+
+pub fn table(comparisons: &[Comparison]) -> String {
+    let rows: Vec<Vec<String>> = comparisons
+        .iter()
+        .map(|comparison| {
+            vec![
+                comparison.day.to_string(),
+                comparison.stage.to_string(),
+                timing::format_duration(Some(comparison.baseline)),
+                timing::format_duration(Some(comparison.current)),
+                format!("{:+.1}%", comparison.change),
+                if comparison.regressed {
+                    "REGRESSED".to_string()
+                } else {
+                    String::new()
+                },
+            ]
+        })
+        .collect();
+    timing::render(
+        &["day", "stage", "baseline", "current", "change", ""],
+        &rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bench::Stats, solution::Part};
+
+    fn record(revision: &str, day: u8, stage: Stage, median_ms: u64) -> Record {
+        let median = Duration::from_millis(median_ms);
+        Record {
+            revision: revision.to_string(),
+            benchmark: Benchmark {
+                day,
+                stage,
+                stats: Stats {
+                    runs: 10,
+                    min: median,
+                    median,
+                    mean: median,
+                    stddev: Duration::ZERO,
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let records = vec![
+            record("abc123", 6, Stage::Part(Part::Two), 1500),
+            record("def456-dirty", 7, Stage::Parse, 2),
+        ];
+        let mut contents = format!("revision,{CSV_HEADER}\n");
+        for record in &records {
+            contents.push_str(&format!(
+                "{},{}\n",
+                record.revision,
+                record.benchmark.to_csv()
+            ));
+        }
+        assert_eq!(Ok(records), parse(&contents));
+    }
+
+    #[test]
+    fn record_replaces_same_revision() {
+        let mut records = vec![
+            record("a", 6, Stage::Part(Part::Two), 1500),
+            record("b", 6, Stage::Part(Part::Two), 1400),
+        ];
+        let new = record("b", 6, Stage::Part(Part::Two), 900);
+        super::record(&mut records, "b", std::slice::from_ref(&new.benchmark));
+
+        assert_eq!(2, records.len());
+        assert_eq!(vec!["a", "b"], revisions(&records));
+        assert_eq!(new, records[1]);
+    }
+
+    #[test]
+    fn update_without_revision() {
+        let path = std::env::temp_dir().join(format!("bench-history-{}.csv", std::process::id()));
+        let benchmarks = [record("a", 6, Stage::Parse, 1).benchmark];
+
+        assert_eq!(Ok(false), update(&path, None, &benchmarks));
+        assert!(!path.exists());
+
+        assert_eq!(Ok(true), update(&path, Some("a"), &benchmarks));
+        let recorded = read(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Ok(vec![record("a", 6, Stage::Parse, 1)]), recorded);
+    }
+
+    #[test]
+    fn flags_regressions_over_threshold() {
+        let records = vec![
+            record("a", 6, Stage::Part(Part::Two), 1000),
+            record("a", 7, Stage::Part(Part::Two), 1000),
+            record("a", 7, Stage::Parse, 1000),
+            record("b", 6, Stage::Part(Part::Two), 1040),
+            record("b", 7, Stage::Part(Part::Two), 1200),
+            record("b", 7, Stage::Parse, 500),
+        ];
+        let comparisons = compare(&records, "a", "b", 5.0);
+
+        let regressed: Vec<(u8, Stage)> = comparisons
+            .iter()
+            .filter(|comparison| comparison.regressed)
+            .map(|comparison| (comparison.day, comparison.stage))
+            .collect();
+        assert_eq!(vec![(7, Stage::Part(Part::Two))], regressed);
+        assert_eq!(3, comparisons.len());
+    }
+}
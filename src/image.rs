@@ -0,0 +1,372 @@
+//! Writing [`Picture`]s as PPM or PNG images for `--export`.
+//!
+//! The PNG encoder is self contained: the pixels are compressed with fixed Huffman codes and
+//! back references to the pixel to the left or the row above, which is enough for the large flat
+//! areas of a scaled up grid.
+
+use std::io::{self, Write};
+
+use crate::visualize::{Picture, Style};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Format {
+    #[default]
+    Png,
+    /// Binary portable pixmap, uncompressed
+    Ppm,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Ppm => "ppm",
+        }
+    }
+}
+
+pub type Rgb = [u8; 3];
+
+/// The colour of a cell drawn in `style`.
+pub fn colour(style: Style) -> Rgb {
+    match style {
+        Style::Plain => [200, 200, 200],
+        Style::Dim => [40, 40, 40],
+        Style::Highlight => [255, 200, 0],
+        Style::Trail => [0, 150, 200],
+        Style::Actor => [0, 220, 0],
+        Style::Wall => [240, 240, 240],
+        Style::Candidate => [230, 40, 40],
+    }
+}
+
+/// An RGB image, row by row from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// `picture` with every cell drawn as a `scale` by `scale` square in the colour of its style.
+    pub fn from_picture(picture: &Picture, scale: usize) -> Self {
+        let width = picture.width() * scale;
+        let height = picture.height() * scale;
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in picture.rows() {
+            let line: Vec<Rgb> = row
+                .iter()
+                .flat_map(|&(_, style)| std::iter::repeat_n(colour(style), scale))
+                .collect();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn write(&self, out: impl Write, format: Format) -> io::Result<()> {
+        match format {
+            Format::Png => self.write_png(out),
+            Format::Ppm => self.write_ppm(out),
+        }
+    }
+
+    pub fn write_ppm(&self, mut out: impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.pixels.concat())?;
+        out.flush()
+    }
+
+    pub fn write_png(&self, mut out: impl Write) -> io::Result<()> {
+        let too_big = |_| io::Error::new(io::ErrorKind::InvalidInput, "image too big for a PNG");
+        let width = u32::try_from(self.width).map_err(too_big)?;
+        let height = u32::try_from(self.height).map_err(too_big)?;
+
+        out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        // 8 bit RGB, deflate, no filtering, no interlacing
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_chunk(&mut out, b"IHDR", &header)?;
+
+        // each row starts with the filter type, which is always none
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            raw.push(0);
+            raw.extend_from_slice(&row.concat());
+        }
+        write_chunk(&mut out, b"IDAT", &zlib(&raw, self.width * 3 + 1))?;
+        write_chunk(&mut out, b"IEND", &[])?;
+        out.flush()
+    }
+}
+
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk too big"))?;
+    out.write_all(&len.to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(&[kind.as_slice(), data].concat());
+    out.write_all(&crc.to_be_bytes())
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1, 0);
+    // 5552 bytes is the most that can be summed before `b` could overflow
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const MAX_MATCH: usize = 258;
+const MAX_DISTANCE: usize = 32768;
+
+/// The index of the last base that `value` is at least, and how far past it `value` is.
+fn code_for(bases: &[u16], value: usize) -> (usize, usize) {
+    let idx = bases.partition_point(|&base| base as usize <= value) - 1;
+    (idx, value - bases[idx] as usize)
+}
+
+/// Packs bits into bytes starting from the least significant bit, as deflate does.
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    buffer: u32,
+    len: u8,
+}
+
+impl Bits {
+    fn push(&mut self, value: u32, len: u8) {
+        for bit in 0..len {
+            self.buffer |= ((value >> bit) & 1) << self.len;
+            self.len += 1;
+            if self.len == 8 {
+                self.bytes.push(self.buffer as u8);
+                self.buffer = 0;
+                self.len = 0;
+            }
+        }
+    }
+
+    /// Huffman codes are packed starting from their most significant bit.
+    fn push_code(&mut self, code: u32, len: u8) {
+        self.push(code.reverse_bits() >> (32 - len), len);
+    }
+
+    /// The fixed Huffman code for a literal/length symbol.
+    fn push_symbol(&mut self, symbol: u16) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.push_code(0x30 + symbol, 8),
+            144..=255 => self.push_code(0x190 + symbol - 144, 9),
+            256..=279 => self.push_code(symbol - 256, 7),
+            _ => self.push_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// The longest match at `data[idx..]` for a back reference `distance` bytes away.
+fn match_len(data: &[u8], idx: usize, distance: usize) -> usize {
+    if distance == 0 || distance > idx || distance > MAX_DISTANCE {
+        return 0;
+    }
+    data[idx..]
+        .iter()
+        .take(MAX_MATCH)
+        .zip(&data[idx - distance..])
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// `data` as a zlib stream of a single deflate block with fixed Huffman codes. Matches are only
+/// looked for one pixel back and one `stride` back.
+fn zlib(data: &[u8], stride: usize) -> Vec<u8> {
+    let mut bits = Bits::default();
+    // final block, fixed Huffman codes
+    bits.push(1, 1);
+    bits.push(1, 2);
+
+    let mut idx = 0;
+    while idx < data.len() {
+        let (len, distance) = [3, stride]
+            .into_iter()
+            .map(|distance| (match_len(data, idx, distance), distance))
+            .max_by_key(|&(len, _)| len)
+            .unwrap_or_default();
+        if len < 3 {
+            bits.push_symbol(data[idx] as u16);
+            idx += 1;
+            continue;
+        }
+
+        let (code, extra) = code_for(&LENGTH_BASE, len);
+        bits.push_symbol(257 + code as u16);
+        bits.push(extra as u32, LENGTH_EXTRA[code]);
+        let (code, extra) = code_for(&DISTANCE_BASE, distance);
+        bits.push_code(code as u32, 5);
+        bits.push(extra as u32, DISTANCE_EXTRA[code]);
+        idx += len;
+    }
+    bits.push_symbol(256);
+
+    // deflate with a 32K window and no preset dictionary
+    let mut stream = vec![0x78, 0x01];
+    stream.extend(bits.finish());
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::Grid, parse};
+
+    fn picture() -> Picture {
+        parse::grid("#.\n.#\n", |_, c| {
+            let style = if c == '#' { Style::Wall } else { Style::Dim };
+            (c, style)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        assert_eq!(0x11e6_0398, adler32(b"Wikipedia"));
+        assert_eq!(1, adler32(&[]));
+        // enough bytes for the sums to need reducing along the way
+        assert_eq!(0xa497_59ea, adler32(&[255; 6000]));
+    }
+
+    #[test]
+    fn scales() {
+        let image = Image::from_picture(&picture(), 2);
+        assert_eq!((4, 4), (image.width(), image.height()));
+        let [wall, dim] = [Style::Wall, Style::Dim].map(colour);
+        assert_eq!(
+            vec![wall, wall, dim, dim, wall, wall, dim, dim],
+            image.pixels[..8]
+        );
+        assert_eq!(vec![dim, dim, wall, wall], image.pixels[12..]);
+    }
+
+    #[test]
+    fn ppm() {
+        let mut out = Vec::new();
+        Image::from_picture(&picture(), 1)
+            .write_ppm(&mut out)
+            .unwrap();
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        for style in [Style::Wall, Style::Dim, Style::Dim, Style::Wall] {
+            expected.extend_from_slice(&colour(style));
+        }
+        assert_eq!(expected, out);
+    }
+
+    #[test]
+    fn png_chunks() {
+        let mut out = Vec::new();
+        Image::from_picture(&picture(), 3)
+            .write_png(&mut out)
+            .unwrap();
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &out[..8]);
+        assert_eq!(
+            b"\0\0\0\x0dIHDR\0\0\0\x06\0\0\0\x06\x08\x02\0\0\0",
+            &out[8..29]
+        );
+        assert_eq!(b"\0\0\0\0IEND\xae\x42\x60\x82", &out[out.len() - 12..]);
+    }
+
+    #[test]
+    fn codes() {
+        assert_eq!((0, 0), code_for(&LENGTH_BASE, 3));
+        assert_eq!((9, 1), code_for(&LENGTH_BASE, 14));
+        assert_eq!((27, 30), code_for(&LENGTH_BASE, 257));
+        assert_eq!((28, 0), code_for(&LENGTH_BASE, 258));
+        assert_eq!((2, 0), code_for(&DISTANCE_BASE, 3));
+        assert_eq!((29, 8191), code_for(&DISTANCE_BASE, 32768));
+    }
+
+    #[test]
+    fn compresses_flat_areas() {
+        let picture = Grid::new(130, 130, ('.', Style::Dim));
+        let raw = (130 * 4 * 3 + 1) * 130 * 4;
+        let mut out = Vec::new();
+        Image::from_picture(&picture, 4)
+            .write_png(&mut out)
+            .unwrap();
+        assert!(out.len() < raw / 50, "{} bytes", out.len());
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    fmt,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+/// Where puzzle inputs are read from at runtime.
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// A directory containing one file per day, named after the day number (`inputs/6`).
+    Dir(PathBuf),
+    /// A single file used for every day that is run.
+    File(PathBuf),
+    /// Standard input, read once and used for every day that is run.
+    Stdin,
+}
+
+impl Source {
+    /// `-` means stdin, anything else is treated as a file path.
+    pub fn from_arg(arg: &Path) -> Self {
+        if arg == Path::new("-") {
+            Self::Stdin
+        } else {
+            Self::File(arg.to_path_buf())
+        }
+    }
+}
+
+/// Loads puzzle inputs from a [`Source`], caching stdin so that it can be shared between days.
+#[derive(Debug)]
+pub struct Inputs {
+    source: Source,
+    stdin: Option<String>,
+}
+
+impl Inputs {
+    pub fn new(source: Source) -> Self {
+        Self {
+            source,
+            stdin: None,
+        }
+    }
+
+    /// Identifies the input used for `day`: the path it is read from, or `-` for stdin.
+    pub fn name(&self, day: u8) -> String {
+        match &self.source {
+            Source::Dir(dir) => dir.join(day.to_string()).display().to_string(),
+            Source::File(path) => path.display().to_string(),
+            Source::Stdin => "-".to_string(),
+        }
+    }
+
+    pub fn load(&mut self, day: u8) -> Result<String, Error> {
+        match &self.source {
+            Source::Dir(dir) => read_file(&dir.join(day.to_string())),
+            Source::File(path) => read_file(path),
+            Source::Stdin => {
+                if let Some(input) = &self.stdin {
+                    return Ok(input.clone());
+                }
+                let mut input = String::new();
+                io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(|source| Error { path: None, source })?;
+                self.stdin = Some(input.clone());
+                Ok(input)
+            }
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, Error> {
+    std::fs::read_to_string(path).map_err(|source| Error {
+        path: Some(path.to_path_buf()),
+        source,
+    })
+}
+
+#[derive(Debug)]
+pub struct Error {
+    /// `None` when reading from stdin.
+    path: Option<PathBuf>,
+    source: io::Error,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(
+                f,
+                "failed to read input {}: {}",
+                path.display(),
+                self.source
+            ),
+            None => write!(f, "failed to read input from stdin: {}", self.source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_is_named_in_error() {
+        let mut inputs = Inputs::new(Source::Dir(PathBuf::from("does-not-exist")));
+        let err = inputs.load(6).unwrap_err();
+        let path = Path::new("does-not-exist").join("6");
+        assert!(
+            err.to_string().contains(&path.display().to_string()),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn dash_is_stdin() {
+        assert!(matches!(Source::from_arg(Path::new("-")), Source::Stdin));
+        assert!(matches!(
+            Source::from_arg(Path::new("inputs/1")),
+            Source::File(_)
+        ));
+    }
+}
@@ -0,0 +1,33 @@
+//! Solutions to Advent of Code 2024.
+//!
+//! Each `dayN` module exposes its parser, domain types and solvers, and implements
+//! [`solution::Solution`] so that every day can be driven the same way through
+//! [`solution::REGISTRY`]. The remaining modules are the machinery behind the command line
+//! runner: loading inputs, timing, benchmarking, checking and reporting answers.
+
+pub mod answer;
+pub mod bench;
+pub mod bitset;
+pub mod check;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod direction;
+pub mod graph;
+pub mod grid;
+pub mod history;
+pub mod image;
+pub mod input;
+pub mod parse;
+pub mod report;
+pub mod runner;
+pub mod search;
+pub mod selection;
+pub mod simulation;
+pub mod solution;
+pub mod timing;
+pub mod visualize;
@@ -1,28 +1,525 @@
-use clap::Parser;
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use adventofcode_2024::{
+    bench,
+    check::{Answers, Outcome},
+    history,
+    image::{self, Image},
+    input::{Inputs, Source},
+    parse::Mode,
+    report::{self, Entry, Format, TableFormat},
+    runner, selection,
+    solution::{Day, Parsed, Part},
+    timing,
+    visualize::{self, Picture},
+};
+use clap::{Parser, Subcommand};
 
 #[derive(Debug, Parser)]
 struct Options {
-    #[arg(short, long)]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Also run the slow days when no days are selected
+    #[arg(short, long, global = true)]
     slow: bool,
+
+    /// Days to run, e.g. `3`, `1..=5` or `1-5`; may be repeated or comma separated
+    #[arg(short, long, global = true, value_parser = selection::parse_days, value_delimiter = ',', conflicts_with = "all")]
+    day: Vec<RangeInclusive<u8>>,
+
+    /// Only run this part of each selected day
+    #[arg(short, long, global = true)]
+    part: Option<Part>,
+
+    /// Run every day, including the slow ones
+    #[arg(short, long, global = true)]
+    all: bool,
+
+    /// Directory containing one input file per day, named after the day number
+    #[arg(
+        long,
+        global = true,
+        default_value = "inputs",
+        conflicts_with = "input"
+    )]
+    inputs: PathBuf,
+
+    /// Read the input for every day from this file instead, or from stdin if `-`
+    #[arg(short, long, global = true)]
+    input: Option<PathBuf>,
+
+    /// Report malformed input lines as errors instead of skipping them
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Report how long parsing and each part took
+    #[arg(short, long)]
+    time: bool,
+
+    /// Check each answer against the known answers file
+    #[arg(short, long)]
+    check: bool,
+
+    /// Known answers as CSV with the columns `input,day,part,answer`
+    #[arg(long, default_value = "answers.csv")]
+    answers: PathBuf,
+
+    /// How to print the answers
+    #[arg(short, long, value_enum, default_value_t)]
+    format: Format,
+
+    /// Draw each part's solution in ANSI colour, for the days that support it
+    #[arg(long)]
+    visualize: bool,
+
+    /// Play back each day step by step in the terminal, for the days that support it
+    #[arg(long)]
+    animate: bool,
+
+    /// Frames per second for `--animate`
+    #[arg(long, default_value_t = 30.0, requires = "animate")]
+    speed: f64,
+
+    /// Write an image of each part's solution to this directory, for the days that support it
+    #[arg(long)]
+    export: Option<PathBuf>,
+
+    /// Also write every frame of the step by step playback to the `--export` directory
+    #[arg(long, requires = "export")]
+    frames: bool,
+
+    /// Image format for `--export`
+    #[arg(long, value_enum, default_value_t, requires = "export")]
+    image_format: image::Format,
+
+    /// Width and height in pixels of each grid cell in exported images
+    #[arg(long, default_value_t = 4, requires = "export")]
+    scale: usize,
+
+    /// Write tables of the steps behind the answers to this directory, for days that support it
+    #[arg(long)]
+    trace: Option<PathBuf>,
+
+    /// Table format for `--trace`
+    #[arg(long, value_enum, default_value_t, requires = "trace")]
+    trace_format: TableFormat,
 }
 
-fn main() {
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run each selected day and part repeatedly and report timing statistics
+    Bench {
+        /// Number of timed runs of each stage
+        #[arg(short = 'n', long, default_value_t = 10)]
+        runs: usize,
+
+        /// Number of untimed runs of each stage before timing starts
+        #[arg(short, long, default_value_t = 3)]
+        warmup: usize,
+
+        /// CSV file to write the results to
+        #[arg(short, long, default_value = "bench.csv")]
+        output: PathBuf,
+
+        /// CSV file accumulating results per git revision
+        #[arg(long, default_value = "bench-history.csv")]
+        history: PathBuf,
+    },
+    /// Compare the median times of two revisions in the benchmark history
+    Compare {
+        /// Revision to compare against, defaults to the one recorded before `--revision`
+        #[arg(short, long)]
+        baseline: Option<String>,
+
+        /// Revision to check, defaults to the most recently recorded
+        #[arg(short, long)]
+        revision: Option<String>,
+
+        /// Percentage slowdown of the median beyond which a stage counts as regressed
+        #[arg(long, default_value_t = 5.0)]
+        threshold: f64,
+
+        /// CSV file accumulating results per git revision
+        #[arg(long, default_value = "bench-history.csv")]
+        history: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
     let opts = Options::parse();
 
-    day1::main();
-    day2::main();
-    day3::main();
-    day4::main();
-    day5::main();
-    if opts.slow {
-        day6::main();
-        day7::main();
+    let result = match &opts.command {
+        None => run(&opts),
+        Some(Command::Bench {
+            runs,
+            warmup,
+            output,
+            history,
+        }) => bench(&opts, *warmup, *runs, output, history),
+        Some(Command::Compare {
+            baseline,
+            revision,
+            threshold,
+            history,
+        }) => compare(
+            history,
+            baseline.as_deref(),
+            revision.as_deref(),
+            *threshold,
+        ),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            if !err.is_empty() {
+                eprintln!("{err}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+struct LoadedInput {
+    day: &'static Day,
+    /// Where the input came from, see [`Inputs::name`].
+    name: String,
+    input: String,
+}
+
+/// The inputs of the selected days that could be loaded, and errors for those that couldn't.
+fn load_inputs(opts: &Options) -> (Vec<LoadedInput>, Vec<(u8, String)>) {
+    let source = match &opts.input {
+        Some(path) => Source::from_arg(path),
+        None => Source::Dir(opts.inputs.clone()),
+    };
+    let mut inputs = Inputs::new(source);
+
+    let mut loaded = Vec::new();
+    let mut errors = Vec::new();
+    for day in selection::selected_days(&opts.day, opts.all, opts.slow) {
+        match inputs.load(day.day) {
+            Ok(input) => loaded.push(LoadedInput {
+                day,
+                name: inputs.name(day.day),
+                input,
+            }),
+            Err(err) => errors.push((day.day, err.to_string())),
+        }
+    }
+    (loaded, errors)
+}
+
+fn selected_parts(opts: &Options) -> Vec<Part> {
+    match opts.part {
+        Some(part) => vec![part],
+        None => Part::ALL.to_vec(),
+    }
+}
+
+fn parse_mode(opts: &Options) -> Mode {
+    if opts.strict {
+        Mode::Strict
+    } else {
+        Mode::Lenient
+    }
+}
+
+fn run(opts: &Options) -> Result<(), String> {
+    let answers = if opts.check {
+        Some(Answers::read(&opts.answers)?)
+    } else {
+        None
+    };
+    let text = opts.format == Format::Text;
+    if opts.speed <= 0.0 {
+        return Err("--speed must be positive".to_string());
+    }
+    if opts.scale == 0 {
+        return Err("--scale must be positive".to_string());
+    }
+    for dir in [&opts.export, &opts.trace].into_iter().flatten() {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("failed to create {}: {err}", dir.display()))?;
+    }
+
+    let (loaded, errors) = load_inputs(opts);
+    let mut entries: Vec<Entry> = errors
+        .into_iter()
+        .map(|(day, error)| Entry::error(day, error))
+        .collect();
+    if text {
+        for entry in &entries {
+            eprintln!("{}", entry.text());
+        }
+    }
+    let parts = selected_parts(opts);
+
+    let mut results = Vec::new();
+    for LoadedInput { day, name, input } in &loaded {
+        let result = match runner::run_day(day, input, &parts, parse_mode(opts)) {
+            Ok(result) => result,
+            Err(err) => {
+                let entry = Entry::error(day.day, format!("{name}: {err}"));
+                if text {
+                    eprintln!("{}", entry.text());
+                }
+                entries.push(entry);
+                continue;
+            }
+        };
+        for part in &result.parts {
+            let entry = match &part.answer {
+                Ok(answer) => Entry {
+                    day: result.day,
+                    part: Some(part.part),
+                    answer: Some(answer.clone()),
+                    parse_time: opts.time.then_some(result.parse_time),
+                    time: opts.time.then_some(part.time),
+                    outcome: answers
+                        .as_ref()
+                        .map(|answers| answers.check(name, result.day, part.part, answer)),
+                    error: None,
+                },
+                Err(err) => Entry {
+                    part: Some(part.part),
+                    ..Entry::error(result.day, format!("{name}: {err}"))
+                },
+            };
+            if text {
+                if entry.error.is_some() {
+                    eprintln!("{}", entry.text());
+                } else {
+                    println!("{}", entry.text());
+                }
+            }
+            entries.push(entry);
+        }
+        if opts.visualize || opts.animate || opts.export.is_some() || opts.trace.is_some() {
+            show(opts, day, input, &parts)
+                .map_err(|err| format!("failed to draw day {}: {err}", day.day))?;
+        }
+        results.push(result);
+    }
+
+    entries.sort_by_key(|entry| (entry.day, entry.part));
+    match opts.format {
+        Format::Text => {
+            if opts.time {
+                println!();
+                print!("{}", timing::table(&results));
+            }
+            if answers.is_some() {
+                let outcomes: Vec<&Outcome> = entries
+                    .iter()
+                    .filter_map(|entry| entry.outcome.as_ref())
+                    .collect();
+                let count = |f: fn(&Outcome) -> bool| outcomes.iter().filter(|o| f(o)).count();
+                println!();
+                println!(
+                    "{} passed, {} failed, {} missing",
+                    count(|outcome| *outcome == Outcome::Pass),
+                    count(|outcome| matches!(outcome, Outcome::Fail { .. })),
+                    count(|outcome| *outcome == Outcome::Missing)
+                );
+            }
+        }
+        Format::Json => print!("{}", report::json(&entries)),
+        Format::Csv => print!("{}", report::csv(&entries)),
+    }
+
+    let failed = entries
+        .iter()
+        .any(|entry| entry.error.is_some() || matches!(entry.outcome, Some(Outcome::Fail { .. })));
+    if failed {
+        // already reported
+        Err(String::new())
+    } else {
+        Ok(())
+    }
+}
+
+/// Draws, animates, exports and traces a day for `--visualize`, `--animate`, `--export` and
+/// `--trace`. The drawings go to stdout in text format, and to stderr otherwise to keep the
+/// answers machine readable.
+fn show(opts: &Options, day: &Day, input: &str, parts: &[Part]) -> io::Result<()> {
+    // the day has already been parsed once to get the answers
+    let Ok(parsed) = day.parse(input, parse_mode(opts)) else {
+        return Ok(());
+    };
+    let mut out: Box<dyn Write> = if opts.format == Format::Text {
+        Box::new(io::stdout().lock())
+    } else {
+        Box::new(io::stderr().lock())
+    };
+
+    if opts.visualize {
+        for &part in parts {
+            match parsed.visualize(part) {
+                Some(picture) => {
+                    let drawing = visualize::ansi(&picture);
+                    write!(out, "day {} part {part}:\n{drawing}", day.day)?
+                }
+                None => eprintln!("day {} part {part} has no visualization", day.day),
+            }
+        }
+    }
+    if opts.animate {
+        match parsed.animate() {
+            Some(frames) => {
+                let frames = frames.map(|picture| visualize::ansi(&picture));
+                visualize::play(&mut out, frames, opts.speed)?
+            }
+            None => eprintln!("day {} has no animation", day.day),
+        }
+    }
+    out.flush()?;
+
+    if let Some(dir) = &opts.export {
+        export_day(opts, dir, day, &*parsed, parts)?;
+    }
+    if let Some(dir) = &opts.trace {
+        match parsed.trace() {
+            Some(tables) => {
+                for table in tables {
+                    let name = format!("day{}-{}", day.day, table.name);
+                    let path = dir.join(name).with_extension(opts.trace_format.extension());
+                    fs::write(&path, table.render(opts.trace_format)).map_err(|err| {
+                        io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+                    })?;
+                }
+            }
+            None => eprintln!("day {} has no trace", day.day),
+        }
+    }
+    Ok(())
+}
+
+/// Writes images of each part and, with `--frames`, of each frame of the animation to `dir`.
+fn export_day(
+    opts: &Options,
+    dir: &Path,
+    day: &Day,
+    parsed: &dyn Parsed,
+    parts: &[Part],
+) -> io::Result<()> {
+    for &part in parts {
+        match parsed.visualize(part) {
+            Some(picture) => export(opts, dir, &format!("day{}-part{part}", day.day), &picture)?,
+            None => eprintln!("day {} part {part} has no visualization", day.day),
+        }
+    }
+    if opts.frames {
+        match parsed.animate() {
+            Some(frames) => {
+                for (idx, picture) in frames.enumerate() {
+                    export(
+                        opts,
+                        dir,
+                        &format!("day{}-frame{idx:05}", day.day),
+                        &picture,
+                    )?;
+                }
+            }
+            None => eprintln!("day {} has no animation", day.day),
+        }
+    }
+    Ok(())
+}
+
+/// Writes `picture` as an image called `name` in `dir`.
+fn export(opts: &Options, dir: &Path, name: &str, picture: &Picture) -> io::Result<()> {
+    let path = dir.join(name).with_extension(opts.image_format.extension());
+    let image = Image::from_picture(picture, opts.scale);
+    File::create(&path)
+        .and_then(|file| image.write(BufWriter::new(file), opts.image_format))
+        .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))
+}
+
+fn bench(
+    opts: &Options,
+    warmup: usize,
+    runs: usize,
+    output: &Path,
+    history_path: &Path,
+) -> Result<(), String> {
+    let (loaded, errors) = load_inputs(opts);
+    if !errors.is_empty() {
+        let errors: Vec<String> = errors
+            .into_iter()
+            .map(|(day, error)| format!("day {day}: {error}"))
+            .collect();
+        return Err(errors.join("\n"));
+    }
+    let parts = selected_parts(opts);
+
+    let mut benchmarks = Vec::new();
+    for LoadedInput { day, name, input } in &loaded {
+        let day_benchmarks = bench::bench_day(day, input, &parts, warmup, runs, parse_mode(opts))
+            .map_err(|err| format!("day {}: {name}: {err}", day.day))?;
+        benchmarks.extend(day_benchmarks);
+    }
+    print!("{}", bench::table(&benchmarks));
+
+    let file = File::create(output)
+        .map_err(|err| format!("failed to create {}: {err}", output.display()))?;
+    bench::write_csv(BufWriter::new(file), &benchmarks)
+        .map_err(|err| format!("failed to write {}: {err}", output.display()))?;
+
+    if !history::update(history_path, history::build_revision(), &benchmarks)? {
+        eprintln!("not recording history, the binary wasn't built in a git checkout");
+    }
+    Ok(())
+}
+
+fn compare(
+    history_path: &Path,
+    baseline: Option<&str>,
+    revision: Option<&str>,
+    threshold: f64,
+) -> Result<(), String> {
+    let records = history::read(history_path)?;
+    let revisions = history::revisions(&records);
+
+    let revision = match revision {
+        Some(revision) => revision,
+        None => revisions
+            .last()
+            .copied()
+            .ok_or_else(|| format!("no benchmarks recorded in {}", history_path.display()))?,
+    };
+    let position = revisions
+        .iter()
+        .position(|rev| *rev == revision)
+        .ok_or_else(|| format!("no benchmarks recorded for {revision}"))?;
+    let baseline = match baseline {
+        Some(baseline) if revisions.contains(&baseline) => baseline,
+        Some(baseline) => return Err(format!("no benchmarks recorded for {baseline}")),
+        None => position
+            .checked_sub(1)
+            .map(|idx| revisions[idx])
+            .ok_or_else(|| format!("no revision recorded before {revision} to compare with"))?,
+    };
+
+    let comparisons = history::compare(&records, baseline, revision, threshold);
+    println!("{baseline} -> {revision}");
+    print!("{}", history::table(&comparisons));
+
+    let regressions = comparisons
+        .iter()
+        .filter(|comparison| comparison.regressed)
+        .count();
+    if regressions > 0 {
+        return Err(format!(
+            "{regressions} stage(s) regressed by more than {threshold}%"
+        ));
     }
+    Ok(())
 }
@@ -0,0 +1,424 @@
+//! Helpers shared by the days' input parsers.
+//!
+//! Line-based inputs go through [`lines`], which handles blank lines, [`Mode`] and locating
+//! errors. The nom parsers here ([`integer`], [`integers`], [`list`], [`pair`],
+//! [`key_values`]) cover the common line shapes and are turned into line parsers with
+//! [`complete`]. Inputs made of several blocks are split with [`sections`], and character
+//! grids are read with [`grid`].
+
+use std::{fmt, str::FromStr};
+
+use nom::{
+    bytes::complete::take_till1,
+    character::complete::{char, digit1, space0, space1},
+    combinator::{map_res, opt, recognize, value},
+    error::ErrorKind,
+    multi::separated_list1,
+    sequence::{pair as both, separated_pair, tuple},
+    IResult,
+};
+
+use crate::grid::{Grid, Position};
+
+/// What to do with lines that can't be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Skip malformed lines.
+    #[default]
+    Lenient,
+    /// Fail on the first malformed line.
+    Strict,
+}
+
+/// A malformed piece of input, located by 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    /// The offending text.
+    pub text: String,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}: `{}`",
+            self.line, self.column, self.message, self.text
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An error within a single line, before the line number is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    /// Byte offset of the offending text in the line that was parsed.
+    offset: usize,
+    text: String,
+    message: String,
+}
+
+impl LineError {
+    /// `text` should be a slice of `line`; anything else is reported at the end of the line.
+    pub fn new(line: &str, text: &str, message: impl Into<String>) -> Self {
+        let start = line.as_ptr() as usize;
+        let offset = (text.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|offset| offset + text.len() <= line.len())
+            .unwrap_or(line.len());
+        Self {
+            offset,
+            text: text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Locates the error in the untrimmed `line`, given the line's 0-based index in the input
+    /// and the trimmed slice of it that was parsed.
+    fn locate(self, idx: usize, line: &str, parsed: &str) -> ParseError {
+        let trimmed = parsed.as_ptr() as usize - line.as_ptr() as usize;
+        let column = line[..trimmed + self.offset].chars().count() + 1;
+        ParseError {
+            line: idx + 1,
+            column,
+            text: self.text,
+            message: self.message,
+        }
+    }
+}
+
+/// Parses each non-blank, trimmed line of `input` with `parse_line`.
+///
+/// In [`Mode::Lenient`] lines that fail to parse are skipped, in [`Mode::Strict`] the first one
+/// is returned as an error.
+pub fn lines<T>(
+    input: &str,
+    mode: Mode,
+    mut parse_line: impl FnMut(&str) -> Result<T, LineError>,
+) -> Result<Vec<T>, ParseError> {
+    let mut parsed = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        match parse_line(trimmed) {
+            Ok(value) => parsed.push(value),
+            Err(err) if mode == Mode::Strict => return Err(err.locate(idx, line, trimmed)),
+            Err(_) => {}
+        }
+    }
+    Ok(parsed)
+}
+
+/// A blank-line-separated block of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    /// 0-based index of the section's first line in the whole input.
+    first_line: usize,
+    pub text: &'a str,
+}
+
+impl Section<'_> {
+    /// [`lines`] for this section, with errors located in the whole input.
+    pub fn lines<T>(
+        &self,
+        mode: Mode,
+        parse_line: impl FnMut(&str) -> Result<T, LineError>,
+    ) -> Result<Vec<T>, ParseError> {
+        lines(self.text, mode, parse_line).map_err(|err| ParseError {
+            line: err.line + self.first_line,
+            ..err
+        })
+    }
+}
+
+/// Splits `input` into exactly `N` blocks of lines separated by blank lines.
+pub fn sections<const N: usize>(input: &str) -> Result<[Section<'_>; N], ParseError> {
+    let mut found: Vec<Section> = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    let mut end = 0;
+    for (idx, line) in input.lines().enumerate() {
+        let start = line.as_ptr() as usize - input.as_ptr() as usize;
+        if line.trim().is_empty() {
+            if let Some((first_line, first)) = current.take() {
+                found.push(Section {
+                    first_line,
+                    text: &input[first..end],
+                });
+            }
+            continue;
+        }
+        current.get_or_insert((idx, start));
+        end = start + line.len();
+    }
+    if let Some((first_line, first)) = current {
+        found.push(Section {
+            first_line,
+            text: &input[first..end],
+        });
+    }
+
+    let count = found.len();
+    found.try_into().map_err(|found: Vec<Section>| {
+        let (line, text) = match found.get(N) {
+            Some(extra) => (
+                extra.first_line + 1,
+                extra.text.lines().next().unwrap_or(""),
+            ),
+            None => (input.lines().count() + 1, ""),
+        };
+        ParseError {
+            line,
+            column: 1,
+            text: text.to_string(),
+            message: format!("expected {N} sections separated by blank lines, found {count}"),
+        }
+    })
+}
+
+/// Parses one cell per character, one row per line.
+///
+/// Whitespace around each row (including `\r`) and trailing blank lines are ignored. Every row
+/// must be as wide as the first that isn't blank, so a blank line before or within the grid is an
+/// error.
+pub fn grid<T>(
+    input: &str,
+    mut cell: impl FnMut(Position, char) -> T,
+) -> Result<Grid<T>, ParseError> {
+    let mut lines: Vec<&str> = input.lines().map(str::trim).collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .find(|&width| width > 0)
+        .unwrap_or(0);
+    let mut cells = Vec::with_capacity(width * lines.len());
+    for (y, line) in lines.iter().enumerate() {
+        let line_width = line.chars().count();
+        if line_width != width {
+            return Err(ParseError {
+                line: y + 1,
+                column: line_width.min(width) + 1,
+                text: line.to_string(),
+                message: format!("expected a row {width} wide, found {line_width}"),
+            });
+        }
+        cells.extend(
+            line.chars()
+                .enumerate()
+                .map(|(x, c)| cell(Position::new(x as isize, y as isize), c)),
+        );
+    }
+
+    Ok(Grid::from_vec(width, lines.len(), cells))
+}
+
+/// Adapts a nom parser for [`lines`]. The parser has to consume the whole line, and its
+/// failures become [`LineError`]s pointing at the offending text.
+pub fn complete<T>(
+    mut parser: impl FnMut(&str) -> IResult<&str, T>,
+) -> impl FnMut(&str) -> Result<T, LineError> {
+    move |line| match parser(line) {
+        Ok(("", value)) => Ok(value),
+        Ok((rest, _)) => Err(LineError::new(line, rest, "unexpected trailing text")),
+        Err(nom::Err::Error(err) | nom::Err::Failure(err)) => {
+            let token = &err.input[..err
+                .input
+                .find(char::is_whitespace)
+                .unwrap_or(err.input.len())];
+            let message = match err.code {
+                ErrorKind::Digit | ErrorKind::MapRes => "expected a number",
+                ErrorKind::Char | ErrorKind::Space => "expected a separator",
+                _ => "unexpected text",
+            };
+            Err(LineError::new(line, token, message))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(LineError::new(line, "", "incomplete line")),
+    }
+}
+
+/// Every match of `parser` in `input`, skipping over anything that doesn't match.
+pub fn scan<T>(input: &str, mut parser: impl FnMut(&str) -> IResult<&str, T>) -> Vec<T> {
+    let mut found = Vec::new();
+    let mut remaining = input;
+    while !remaining.is_empty() {
+        match parser(remaining) {
+            Ok((rest, value)) if rest.len() < remaining.len() => {
+                found.push(value);
+                remaining = rest;
+            }
+            _ => {
+                let mut chars = remaining.chars();
+                chars.next();
+                remaining = chars.as_str();
+            }
+        }
+    }
+    found
+}
+
+/// A decimal integer with an optional leading `-`.
+pub fn integer<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(recognize(both(opt(char('-')), digit1)), str::parse)(input)
+}
+
+/// Integers separated by whitespace, like `7 6 4 2 1`.
+pub fn integers<T: FromStr>(input: &str) -> IResult<&str, Vec<T>> {
+    list(' ')(input)
+}
+
+/// Integers separated by `separator`, like `75,47,61`. A `' '` separator matches any run of
+/// spaces and tabs.
+pub fn list<T: FromStr>(separator: char) -> impl FnMut(&str) -> IResult<&str, Vec<T>> {
+    move |input| separated_list1(delimiter(separator), integer)(input)
+}
+
+/// Two integers separated by `separator`, like `47|53` or `3   4`.
+pub fn pair<A: FromStr, B: FromStr>(separator: char) -> impl FnMut(&str) -> IResult<&str, (A, B)> {
+    move |input| separated_pair(integer, delimiter(separator), integer)(input)
+}
+
+/// A `key: values` line with whitespace-separated integer values, like `190: 10 19`.
+pub fn key_values<K: FromStr, V: FromStr>(input: &str) -> IResult<&str, (K, Vec<V>)> {
+    separated_pair(
+        map_res(take_till1(|c| c == ':'), |key: &str| key.trim().parse()),
+        delimiter(':'),
+        integers,
+    )(input)
+}
+
+/// `separator` with optional whitespace around it, or a run of whitespace for `' '`.
+fn delimiter(separator: char) -> impl FnMut(&str) -> IResult<&str, ()> {
+    move |input| {
+        if separator == ' ' {
+            value((), space1)(input)
+        } else {
+            value((), tuple((space0, char(separator), space0)))(input)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn lenient_skips_malformed_lines() {
+        let input = "1|2\nx|3\n\n4|5\n";
+        assert_eq!(
+            Ok(vec![(1, 2), (4, 5)]),
+            lines(input, Mode::Lenient, complete(pair::<u32, u32>('|')))
+        );
+    }
+
+    #[test]
+    fn strict_locates_first_error() {
+        let input = "1|2\n\n  4|y5\n6\n";
+        let expected = ParseError {
+            line: 3,
+            column: 5,
+            text: "y5".to_string(),
+            message: "expected a number".to_string(),
+        };
+        assert_eq!(
+            Err(expected),
+            lines(input, Mode::Strict, complete(pair::<u32, u32>('|')))
+        );
+    }
+
+    #[test]
+    fn display() {
+        let err = lines("7", Mode::Strict, complete(pair::<u32, u32>('|'))).unwrap_err();
+        assert_eq!(
+            "line 1, column 2: expected a separator: ``",
+            err.to_string()
+        );
+    }
+
+    #[test_case("7 6  4\t2", Some(vec![7, 6, 4, 2]))]
+    #[test_case("-3 4", Some(vec![-3, 4]))]
+    #[test_case("1 2 x", None)]
+    #[test_case("1,2", None)]
+    fn whitespace_separated(line: &str, expected: Option<Vec<i32>>) {
+        assert_eq!(expected, complete(integers)(line).ok());
+    }
+
+    #[test_case("75,47,61", Some(vec![75, 47, 61]))]
+    #[test_case("75, 47 ,61", Some(vec![75, 47, 61]) ; "spaced")]
+    #[test_case("75,,61", None)]
+    fn comma_separated(line: &str, expected: Option<Vec<u32>>) {
+        assert_eq!(expected, complete(list(','))(line).ok());
+    }
+
+    #[test]
+    fn pairs() {
+        assert_eq!(Ok((3, 4)), complete(pair::<u32, u32>(' '))("3   4"));
+        assert_eq!(Ok((47, 53)), complete(pair::<u32, u32>('|'))("47|53"));
+        let err = complete(pair::<u32, u32>('|'))("47|53|1").unwrap_err();
+        assert_eq!("unexpected trailing text", err.message);
+    }
+
+    #[test]
+    fn key_value_lines() {
+        assert_eq!(
+            Ok((190, vec![10, 19])),
+            complete(key_values::<u64, u64>)("190: 10 19")
+        );
+        assert!(complete(key_values::<u64, u64>)("190 10 19").is_err());
+    }
+
+    #[test]
+    fn sections_keep_line_numbers() {
+        let input = "1|2\n3|4\n\n\n5,6\nx,7\n";
+        let [rules, updates] = sections(input).unwrap();
+        assert_eq!("1|2\n3|4", rules.text);
+        assert_eq!("5,6\nx,7", updates.text);
+
+        let err = updates
+            .lines(Mode::Strict, complete(list::<u32>(',')))
+            .unwrap_err();
+        assert_eq!((6, 1), (err.line, err.column));
+    }
+
+    #[test]
+    fn wrong_number_of_sections() {
+        let err = sections::<2>("1\n2\n").unwrap_err();
+        assert_eq!(3, err.line);
+        let err = sections::<1>("1\n\n2\n").unwrap_err();
+        assert_eq!((3, "2"), (err.line, err.text.as_str()));
+    }
+
+    #[test]
+    fn scans() {
+        let found: Vec<u32> = scan("a1b22c-x333", |input| {
+            let (input, _) = char('x')(input)?;
+            integer(input)
+        });
+        assert_eq!(vec![333], found);
+        assert_eq!(vec![1, 22, 333], scan("a1b22c-x333", integer::<u32>));
+    }
+
+    #[test]
+    fn grids() {
+        let parsed = grid("ab\r\ncd  \n\n", |_, c| c).unwrap();
+        assert_eq!("ab\ncd\n", parsed.to_string());
+
+        let parsed = grid("  ab\n\tcd\nef\n", |_, c| c).unwrap();
+        assert_eq!("ab\ncd\nef\n", parsed.to_string());
+
+        let err = grid("abc\nde\nfgh", |_, c| c).unwrap_err();
+        assert_eq!((2, 3), (err.line, err.column));
+
+        let err = grid("\nabc\n", |_, c| c).unwrap_err();
+        assert_eq!((1, 1), (err.line, err.column));
+        assert_eq!("expected a row 3 wide, found 0", err.message);
+    }
+}
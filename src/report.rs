@@ -0,0 +1,374 @@
+use std::{fmt::Write, time::Duration};
+
+use crate::{answer::Answer, check::Outcome, solution::Part};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Format {
+    /// `day N part M: answer` lines, with the timing table and check summary after
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+/// One line of output: the answer to a part, or an error that stopped a day from running.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub day: u8,
+    pub part: Option<Part>,
+    pub answer: Option<Answer>,
+    pub parse_time: Option<Duration>,
+    pub time: Option<Duration>,
+    pub outcome: Option<Outcome>,
+    pub error: Option<String>,
+}
+
+impl Entry {
+    pub fn error(day: u8, error: String) -> Self {
+        Self {
+            day,
+            part: None,
+            answer: None,
+            parse_time: None,
+            time: None,
+            outcome: None,
+            error: Some(error),
+        }
+    }
+
+    /// The `text` format line for this entry.
+    pub fn text(&self) -> String {
+        let mut line = format!("day {}", self.day);
+        if let Some(part) = self.part {
+            let _ = write!(line, " part {part}");
+        }
+        if let Some(answer) = &self.answer {
+            let _ = write!(line, ": {answer}");
+        }
+        if let Some(outcome) = &self.outcome {
+            let _ = write!(line, " ({outcome})");
+        }
+        if let Some(error) = &self.error {
+            let _ = write!(line, ": {error}");
+        }
+        line
+    }
+}
+
+/// Renders the entries as a JSON array of objects, leaving out fields that have no value.
+pub fn json(entries: &[Entry]) -> String {
+    let objects: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            let mut fields = vec![format!("\"day\":{}", entry.day)];
+            if let Some(part) = entry.part {
+                fields.push(format!("\"part\":{part}"));
+            }
+            if let Some(answer) = &entry.answer {
+                fields.push(format!("\"answer\":{}", json_answer(answer)));
+            }
+            if let Some(parse_time) = entry.parse_time {
+                fields.push(format!("\"parse_ns\":{}", parse_time.as_nanos()));
+            }
+            if let Some(time) = entry.time {
+                fields.push(format!("\"time_ns\":{}", time.as_nanos()));
+            }
+            if let Some(outcome) = &entry.outcome {
+                fields.push(format!("\"check\":{}", json_string(outcome_name(outcome))));
+                if let Outcome::Fail { expected } = outcome {
+                    fields.push(format!("\"expected\":{}", json_answer(expected)));
+                }
+            }
+            if let Some(error) = &entry.error {
+                fields.push(format!("\"error\":{}", json_string(error)));
+            }
+            format!("  {{{}}}", fields.join(","))
+        })
+        .collect();
+
+    if objects.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+pub const CSV_HEADER: &str = "day,part,answer,parse_ns,time_ns,check,expected,error";
+
+/// Renders the entries as CSV with a header row, leaving fields that have no value empty.
+pub fn csv(entries: &[Entry]) -> String {
+    let mut csv = format!("{CSV_HEADER}\n");
+    for entry in entries {
+        let expected = match &entry.outcome {
+            Some(Outcome::Fail { expected }) => expected.to_string(),
+            _ => String::new(),
+        };
+        let fields = [
+            entry.day.to_string(),
+            optional(entry.part),
+            optional(entry.answer.as_ref()),
+            optional(entry.parse_time.map(|time| time.as_nanos())),
+            optional(entry.time.map(|time| time.as_nanos())),
+            optional(entry.outcome.as_ref().map(outcome_name)),
+            expected,
+            optional(entry.error.as_ref()),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        let _ = writeln!(csv, "{}", fields.join(","));
+    }
+    csv
+}
+
+/// How to write a [`Table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum TableFormat {
+    /// A header row, then one line per row
+    #[default]
+    Csv,
+    /// An array with an object per row
+    Json,
+}
+
+impl TableFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            TableFormat::Csv => "csv",
+            TableFormat::Json => "json",
+        }
+    }
+}
+
+/// A value in a [`Table`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+    Str(String),
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Str(value)
+    }
+}
+
+/// Data other than answers that a day can export, such as the steps of a simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    /// Names the file the table is written to.
+    pub name: &'static str,
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    pub fn new(name: &'static str, columns: &[&'static str]) -> Self {
+        Self {
+            name,
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row with a value for each column.
+    pub fn push(&mut self, row: Vec<Value>) {
+        assert_eq!(self.columns.len(), row.len(), "row doesn't fit the columns");
+        self.rows.push(row);
+    }
+
+    pub fn render(&self, format: TableFormat) -> String {
+        match format {
+            TableFormat::Csv => {
+                let mut csv = format!("{}\n", self.columns.join(","));
+                for row in &self.rows {
+                    let fields: Vec<String> = row
+                        .iter()
+                        .map(|value| match value {
+                            Value::Int(num) => num.to_string(),
+                            Value::Bool(b) => b.to_string(),
+                            Value::Str(s) => csv_field(s),
+                        })
+                        .collect();
+                    let _ = writeln!(csv, "{}", fields.join(","));
+                }
+                csv
+            }
+            TableFormat::Json => {
+                let objects: Vec<String> = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let fields: Vec<String> = self
+                            .columns
+                            .iter()
+                            .zip(row)
+                            .map(|(column, value)| {
+                                let value = match value {
+                                    Value::Int(num) => num.to_string(),
+                                    Value::Bool(b) => b.to_string(),
+                                    Value::Str(s) => json_string(s),
+                                };
+                                format!("{}:{value}", json_string(column))
+                            })
+                            .collect();
+                        format!("  {{{}}}", fields.join(","))
+                    })
+                    .collect();
+                if objects.is_empty() {
+                    "[]\n".to_string()
+                } else {
+                    format!("[\n{}\n]\n", objects.join(",\n"))
+                }
+            }
+        }
+    }
+}
+
+fn optional(value: Option<impl ToString>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn outcome_name(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::Pass => "pass",
+        Outcome::Fail { .. } => "fail",
+        Outcome::Missing => "missing",
+    }
+}
+
+fn json_answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Str(s) => json_string(s),
+        numeric => numeric.to_string(),
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<Entry> {
+        vec![
+            Entry {
+                day: 1,
+                part: Some(Part::One),
+                answer: Some(Answer::U32(11)),
+                parse_time: Some(Duration::from_nanos(1500)),
+                time: Some(Duration::from_nanos(200)),
+                outcome: Some(Outcome::Fail {
+                    expected: Answer::U32(12),
+                }),
+                error: None,
+            },
+            Entry {
+                day: 9,
+                part: Some(Part::Two),
+                answer: Some(Answer::from("1,2")),
+                parse_time: None,
+                time: None,
+                outcome: None,
+                error: None,
+            },
+            Entry::error(6, "failed to read input \"inputs/6\"".to_string()),
+        ]
+    }
+
+    #[test]
+    fn text() {
+        let lines: Vec<String> = entries().iter().map(Entry::text).collect();
+        assert_eq!(
+            vec![
+                "day 1 part 1: 11 (FAIL, expected 12)",
+                "day 9 part 2: 1,2",
+                "day 6: failed to read input \"inputs/6\"",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn json_output() {
+        let expected = r#"[
+  {"day":1,"part":1,"answer":11,"parse_ns":1500,"time_ns":200,"check":"fail","expected":12},
+  {"day":9,"part":2,"answer":"1,2"},
+  {"day":6,"error":"failed to read input \"inputs/6\""}
+]
+"#;
+        assert_eq!(expected, json(&entries()));
+        assert_eq!("[]\n", json(&[]));
+    }
+
+    #[test]
+    fn csv_output() {
+        let expected = format!(
+            r#"{CSV_HEADER}
+1,1,11,1500,200,fail,12,
+9,2,"1,2",,,,,
+6,,,,,,,"failed to read input ""inputs/6"""
+"#
+        );
+        assert_eq!(expected, csv(&entries()));
+    }
+
+    #[test]
+    fn tables() {
+        let mut table = Table::new("steps", &["x", "turned", "note"]);
+        table.push(vec![3.into(), false.into(), "a, \"b\"".to_string().into()]);
+        table.push(vec![(-1).into(), true.into(), String::new().into()]);
+        let csv = r#"x,turned,note
+3,false,"a, ""b"""
+-1,true,
+"#;
+        assert_eq!(csv, table.render(TableFormat::Csv));
+        let json = r#"[
+  {"x":3,"turned":false,"note":"a, \"b\""},
+  {"x":-1,"turned":true,"note":""}
+]
+"#;
+        assert_eq!(json, table.render(TableFormat::Json));
+        assert_eq!(
+            "[]\n",
+            Table::new("empty", &["x"]).render(TableFormat::Json)
+        );
+    }
+}
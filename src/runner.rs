@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    answer::Answer,
+    parse::{Mode, ParseError},
+    solution::{Day, Part, SolveError},
+};
+
+/// The answers and timings from running one day.
+#[derive(Debug)]
+pub struct DayResult {
+    pub day: u8,
+    pub parse_time: Duration,
+    pub parts: Vec<PartResult>,
+}
+
+#[derive(Debug)]
+pub struct PartResult {
+    pub part: Part,
+    pub answer: Result<Answer, SolveError>,
+    pub time: Duration,
+}
+
+impl DayResult {
+    /// Parse time plus the time of every part that was run.
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.parts.iter().map(|part| part.time).sum::<Duration>()
+    }
+}
+
+/// Parses `input` and solves each of `parts`, timing parsing and each part separately.
+pub fn run_day(
+    day: &Day,
+    input: &str,
+    parts: &[Part],
+    mode: Mode,
+) -> Result<DayResult, ParseError> {
+    let start = Instant::now();
+    let parsed = day.parse(input, mode)?;
+    let parse_time = start.elapsed();
+
+    let parts = parts
+        .iter()
+        .map(|&part| {
+            let start = Instant::now();
+            let answer = parsed.solve(part);
+            let time = start.elapsed();
+            PartResult { part, answer, time }
+        })
+        .collect();
+
+    Ok(DayResult {
+        day: day.day,
+        parse_time,
+        parts,
+    })
+}
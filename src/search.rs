@@ -0,0 +1,435 @@
+//! Shortest path searches, generic over the node type and a neighbour function.
+//!
+//! On a [`Grid`](crate::grid::Grid) the nodes are usually [`Position`]s, or positions paired
+//! with a heading, and the neighbours come from [`Grid::neighbours4`](crate::grid::Grid).
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+use crate::grid::Position;
+
+/// A path cost. `Default` has to be zero.
+pub trait Cost: Copy + Ord + Default + Add<Output = Self> {}
+
+impl<C: Copy + Ord + Default + Add<Output = C>> Cost for C {}
+
+/// A path from the start to a goal, both included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N, C> {
+    pub nodes: Vec<N>,
+    pub cost: C,
+}
+
+/// The fewest steps from `start` to a node accepted by `is_goal`, where every step costs 1.
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, usize>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = HashMap::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            let nodes = walk_back(&parents, node);
+            return Some(Path {
+                cost: nodes.len() - 1,
+                nodes,
+            });
+        }
+        for next in neighbours(&node) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// The cheapest path from `start` to a node accepted by `is_goal`. `neighbours` gives each
+/// neighbour with the cost of stepping to it.
+pub fn dijkstra<N, C, I>(
+    start: N,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(start, neighbours, |_| C::default(), is_goal)
+}
+
+/// [`dijkstra`] guided by `heuristic`, an estimate of the remaining cost to a goal. The path is
+/// only guaranteed to be the cheapest if the heuristic never overestimates.
+pub fn astar<N, C, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Path<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut nodes = Nodes::default();
+    let start = nodes.id(start);
+    let mut best = HashMap::from([(start, (C::default(), None))]);
+    let mut queue = BinaryHeap::from([Reverse((heuristic(&nodes[start]), C::default(), start))]);
+
+    while let Some(Reverse((_, cost, id))) = queue.pop() {
+        if best[&id].0 < cost {
+            continue;
+        }
+        if is_goal(&nodes[id]) {
+            let mut path = vec![id];
+            while let Some(parent) = best[path.last()?].1 {
+                path.push(parent);
+            }
+            return Some(Path {
+                nodes: path.iter().rev().map(|&id| nodes[id].clone()).collect(),
+                cost,
+            });
+        }
+        for (next, step) in neighbours(&nodes[id]) {
+            let next_cost = cost + step;
+            let estimate = next_cost + heuristic(&next);
+            let next = nodes.id(next);
+            if best.get(&next).is_none_or(|&(known, _)| next_cost < known) {
+                best.insert(next, (next_cost, Some(id)));
+                queue.push(Reverse((estimate, next_cost, next)));
+            }
+        }
+    }
+    None
+}
+
+/// Every cheapest path from `start` to the nodes accepted by `is_goal`.
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<N, C> {
+    pub cost: C,
+    /// The goals reached at that cost.
+    pub goals: Vec<N>,
+    /// Every node's predecessors on a cheapest path to it.
+    predecessors: HashMap<N, Vec<N>>,
+}
+
+impl<N: Clone + Eq + Hash, C> ShortestPaths<N, C> {
+    /// Every node on any of the paths.
+    pub fn nodes(&self) -> HashSet<N> {
+        let mut seen: HashSet<N> = self.goals.iter().cloned().collect();
+        let mut stack = self.goals.clone();
+        while let Some(node) = stack.pop() {
+            for parent in &self.predecessors[&node] {
+                if seen.insert(parent.clone()) {
+                    stack.push(parent.clone());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Every path, each from the start to a goal. There can be exponentially many.
+    pub fn paths(&self) -> Vec<Vec<N>> {
+        let mut paths = Vec::new();
+        // partial paths, built backwards from a goal
+        let mut stack: Vec<Vec<N>> = self.goals.iter().map(|goal| vec![goal.clone()]).collect();
+        while let Some(path) = stack.pop() {
+            let parents = &self.predecessors[path.last().expect("paths are never empty")];
+            if parents.is_empty() {
+                paths.push(path.into_iter().rev().collect());
+                continue;
+            }
+            for parent in parents {
+                let mut longer = path.clone();
+                longer.push(parent.clone());
+                stack.push(longer);
+            }
+        }
+        paths
+    }
+}
+
+/// Like [`dijkstra`], but keeps every cheapest path to every goal at the lowest cost.
+pub fn all_shortest_paths<N, C, I>(
+    start: N,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<ShortestPaths<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut nodes = Nodes::default();
+    let start = nodes.id(start);
+    let mut best: HashMap<usize, (C, Vec<usize>)> =
+        HashMap::from([(start, (C::default(), vec![]))]);
+    let mut queue = BinaryHeap::from([Reverse((C::default(), start))]);
+    let mut found: Option<(C, Vec<usize>)> = None;
+
+    while let Some(Reverse((cost, id))) = queue.pop() {
+        if best[&id].0 < cost {
+            continue;
+        }
+        // nodes at the goal's cost still get expanded, free steps from them can reach a goal
+        if found
+            .as_ref()
+            .is_some_and(|(goal_cost, _)| cost > *goal_cost)
+        {
+            break;
+        }
+        if is_goal(&nodes[id]) {
+            match &mut found {
+                Some((_, goals)) => goals.push(id),
+                None => found = Some((cost, vec![id])),
+            }
+        }
+        for (next, step) in neighbours(&nodes[id]) {
+            let next_cost = cost + step;
+            let next = nodes.id(next);
+            match best.get(&next) {
+                Some(&(known, _)) if next_cost == known => {
+                    // a free step can lead back to a node on the way here, which would make
+                    // `paths` go round forever
+                    if step > C::default() || !leads_to(&best, next, id) {
+                        best.get_mut(&next).expect("just found").1.push(id);
+                    }
+                }
+                Some(&(known, _)) if next_cost > known => {}
+                _ => {
+                    best.insert(next, (next_cost, vec![id]));
+                    queue.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+    }
+
+    let (cost, goals) = found?;
+    let predecessors = best
+        .into_iter()
+        .map(|(id, (_, parents))| {
+            let parents = parents.into_iter().map(|id| nodes[id].clone()).collect();
+            (nodes[id].clone(), parents)
+        })
+        .collect();
+    Some(ShortestPaths {
+        cost,
+        goals: goals.into_iter().map(|id| nodes[id].clone()).collect(),
+        predecessors,
+    })
+}
+
+/// Whether `to` is `from` or reached from it, going by the predecessors in `best`.
+fn leads_to<C>(best: &HashMap<usize, (C, Vec<usize>)>, from: usize, to: usize) -> bool {
+    let mut seen = HashSet::from([to]);
+    let mut stack = vec![to];
+    while let Some(id) = stack.pop() {
+        if id == from {
+            return true;
+        }
+        for &parent in &best[&id].1 {
+            if seen.insert(parent) {
+                stack.push(parent);
+            }
+        }
+    }
+    false
+}
+
+/// Manhattan distance, an admissible [`astar`] heuristic for 4-way grid movement at cost 1.
+pub fn manhattan(from: Position, to: Position) -> usize {
+    from.x.abs_diff(to.x) + from.y.abs_diff(to.y)
+}
+
+fn walk_back<N: Clone + Eq + Hash>(parents: &HashMap<N, Option<N>>, end: N) -> Vec<N> {
+    let mut path = vec![end];
+    while let Some(Some(parent)) = path.last().map(|node| &parents[node]) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Numbers nodes so the priority queues don't need `N: Ord`.
+struct Nodes<N> {
+    nodes: Vec<N>,
+    ids: HashMap<N, usize>,
+}
+
+impl<N> Default for Nodes<N> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Clone + Eq + Hash> Nodes<N> {
+    fn id(&mut self, node: N) -> usize {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        self.nodes.push(node.clone());
+        self.ids.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+}
+
+impl<N> std::ops::Index<usize> for Nodes<N> {
+    type Output = N;
+
+    fn index(&self, id: usize) -> &N {
+        &self.nodes[id]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::Grid, parse};
+
+    const MAZE: &str = r#"S..#....
+.#.#.##.
+.#...#..
+.####.#.
+......#E
+"#;
+
+    fn maze() -> (Grid<char>, Position, Position) {
+        let grid = parse::grid(MAZE, |_, c| c).unwrap();
+        let find = |target| {
+            grid.iter()
+                .find(|(_, c)| **c == target)
+                .map(|(pos, _)| pos)
+                .unwrap()
+        };
+        let (start, end) = (find('S'), find('E'));
+        (grid, start, end)
+    }
+
+    fn open(grid: &Grid<char>, pos: Position) -> impl Iterator<Item = Position> + '_ {
+        grid.neighbours4(pos)
+            .filter(|(_, c)| **c != '#')
+            .map(|(pos, _)| pos)
+    }
+
+    #[test]
+    fn bfs_maze() {
+        let (grid, start, end) = maze();
+        let path = bfs(start, |&pos| open(&grid, pos), |&pos| pos == end).unwrap();
+        assert_eq!(15, path.cost);
+        assert_eq!(Some(&start), path.nodes.first());
+        assert_eq!(Some(&end), path.nodes.last());
+        assert!(path
+            .nodes
+            .windows(2)
+            .all(|step| manhattan(step[0], step[1]) == 1));
+
+        assert_eq!(None, bfs(start, |&pos| open(&grid, pos), |_| false));
+    }
+
+    #[test]
+    fn weighted_maze() {
+        let (grid, start, end) = maze();
+        // stepping onto the bottom row costs 10, which only the step onto the goal can't avoid
+        let weighted = |&pos: &Position| {
+            open(&grid, pos)
+                .map(|next| (next, if next.y == 4 { 10 } else { 1 }))
+                .collect::<Vec<_>>()
+        };
+
+        let by_dijkstra = dijkstra(start, weighted, |&pos| pos == end).unwrap();
+        let by_astar = astar(
+            start,
+            weighted,
+            |&pos| manhattan(pos, end),
+            |&pos| pos == end,
+        )
+        .unwrap();
+        assert_eq!(by_dijkstra.cost, by_astar.cost);
+        assert_eq!(14 + 10, by_dijkstra.cost);
+        assert!(by_dijkstra.nodes.iter().filter(|pos| pos.y == 4).count() == 1);
+    }
+
+    #[test]
+    fn dijkstra_prefers_cheap_detour() {
+        let edges = HashMap::from([
+            ('a', vec![('b', 1), ('c', 10)]),
+            ('b', vec![('c', 2)]),
+            ('c', vec![]),
+        ]);
+        let path = dijkstra('a', |node| edges[node].clone(), |&node| node == 'c').unwrap();
+        assert_eq!(
+            Path {
+                nodes: vec!['a', 'b', 'c'],
+                cost: 3
+            },
+            path
+        );
+    }
+
+    #[test]
+    fn every_shortest_path() {
+        // two ways round a pillar, plus a longer way round
+        let grid = parse::grid("S...\n.#..\n...E\n", |_, c| c).unwrap();
+        let start = Position::new(0, 0);
+        let end = Position::new(3, 2);
+        let paths = all_shortest_paths(
+            start,
+            |&pos| open(&grid, pos).map(|next| (next, 1)).collect::<Vec<_>>(),
+            |&pos| pos == end,
+        )
+        .unwrap();
+
+        assert_eq!(5, paths.cost);
+        assert_eq!(vec![end], paths.goals);
+        let all = paths.paths();
+        // 10 ways to make 3 moves right and 2 down, 6 of which go through the pillar
+        assert_eq!(10 - 6, all.len());
+        assert!(all.iter().all(|path| path.len() == 6));
+        assert_eq!(11, paths.nodes().len());
+    }
+
+    #[test]
+    fn free_steps_back() {
+        let edges = HashMap::from([
+            ('a', vec![('b', 0), ('c', 1)]),
+            ('b', vec![('a', 0), ('b', 0), ('c', 1)]),
+            ('c', vec![]),
+        ]);
+        let paths =
+            all_shortest_paths('a', |node| edges[node].clone(), |&node| node == 'c').unwrap();
+
+        assert_eq!(1, paths.cost);
+        let mut all = paths.paths();
+        all.sort();
+        assert_eq!(vec![vec!['a', 'b', 'c'], vec!['a', 'c']], all);
+    }
+
+    #[test]
+    fn free_step_onto_goal() {
+        for first in [vec![('a', 1), ('g', 1)], vec![('g', 1), ('a', 1)]] {
+            let edges = HashMap::from([('s', first), ('a', vec![('g', 0)]), ('g', vec![])]);
+            let paths =
+                all_shortest_paths('s', |node| edges[node].clone(), |&node| node == 'g').unwrap();
+
+            assert_eq!(1, paths.cost);
+            assert_eq!(vec!['g'], paths.goals);
+            let mut all = paths.paths();
+            all.sort();
+            assert_eq!(vec![vec!['s', 'a', 'g'], vec!['s', 'g']], all);
+        }
+    }
+}
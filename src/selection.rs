@@ -0,0 +1,106 @@
+use std::{collections::BTreeSet, ops::RangeInclusive};
+
+use crate::solution::{self, Day, REGISTRY};
+
+/// Parses a `--day` argument: a single day (`3`), an inclusive range (`1..=5` or `1-5`) or an
+/// exclusive range (`1..6`).
+pub fn parse_days(arg: &str) -> Result<RangeInclusive<u8>, String> {
+    let arg = arg.trim();
+    let (start, end) = if let Some((start, end)) = arg.split_once("..=") {
+        (parse_day(start)?, parse_day(end)?)
+    } else if let Some((start, end)) = arg.split_once("..") {
+        let end = parse_day(end)?;
+        let end = end
+            .checked_sub(1)
+            .ok_or_else(|| format!("empty day range `{arg}`"))?;
+        (parse_day(start)?, end)
+    } else if let Some((start, end)) = arg.split_once('-') {
+        (parse_day(start)?, parse_day(end)?)
+    } else {
+        let day = parse_day(arg)?;
+        (day, day)
+    };
+
+    if start > end {
+        return Err(format!("empty day range `{arg}`"));
+    }
+    if let Some(day) = (start..=end).find(|day| solution::find(*day).is_none()) {
+        return Err(format!("day {day} is not solved yet"));
+    }
+    Ok(start..=end)
+}
+
+fn parse_day(day: &str) -> Result<u8, String> {
+    day.trim()
+        .parse()
+        .map_err(|_| format!("`{day}` is not a day number"))
+}
+
+/// Works out which days to run from the command line options.
+///
+/// Explicitly requested days are always run; otherwise every day is run, skipping the slow ones
+/// unless `slow` is set.
+pub fn selected_days(days: &[RangeInclusive<u8>], all: bool, slow: bool) -> Vec<&'static Day> {
+    if all {
+        return REGISTRY.iter().collect();
+    }
+    if !days.is_empty() {
+        let days: BTreeSet<u8> = days.iter().flat_map(|range| range.clone()).collect();
+        return REGISTRY
+            .iter()
+            .filter(|entry| days.contains(&entry.day))
+            .collect();
+    }
+    REGISTRY
+        .iter()
+        .filter(|entry| slow || !entry.slow)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("3", 3..=3)]
+    #[test_case("1..=5", 1..=5)]
+    #[test_case("1..5", 1..=4)]
+    #[test_case("2-4", 2..=4)]
+    #[test_case(" 7 ", 7..=7)]
+    fn parse(arg: &str, expected: RangeInclusive<u8>) {
+        assert_eq!(Ok(expected), parse_days(arg));
+    }
+
+    #[test_case("0")]
+    #[test_case("8")]
+    #[test_case("5..=3")]
+    #[test_case("1..1")]
+    #[test_case("x")]
+    fn parse_invalid(arg: &str) {
+        assert!(parse_days(arg).is_err(), "{arg}");
+    }
+
+    fn day_numbers(days: Vec<&Day>) -> Vec<u8> {
+        days.into_iter().map(|entry| entry.day).collect()
+    }
+
+    #[test]
+    fn default_skips_slow_days() {
+        assert_eq!(
+            vec![1, 2, 3, 4, 5, 6],
+            day_numbers(selected_days(&[], false, false))
+        );
+        assert_eq!(
+            vec![1, 2, 3, 4, 5, 6, 7],
+            day_numbers(selected_days(&[], false, true))
+        );
+    }
+
+    #[test]
+    fn explicit_days_ignore_slow() {
+        assert_eq!(
+            vec![2, 7],
+            day_numbers(selected_days(&[7..=7, 2..=2], false, false))
+        );
+    }
+}
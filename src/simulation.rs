@@ -0,0 +1,263 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A deterministic system that moves from one state to the next.
+pub trait State: Sized + Clone + Eq {
+    /// The state after this one, or `None` if the simulation has ended.
+    fn step(&self) -> Option<Self>;
+}
+
+/// How a simulation run from some initial state turns out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome<S> {
+    /// The simulation ended after `steps` steps in state `last`.
+    Halts { steps: usize, last: S },
+    /// The simulation repeats forever.
+    Loops(Cycle<S>),
+}
+
+/// A cycle reached from an initial state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<S> {
+    /// Steps from the initial state to the first state on the cycle, which is also the length of
+    /// the transient prefix before it.
+    pub start: usize,
+    pub length: usize,
+    /// The first state on the cycle.
+    pub entry: S,
+}
+
+impl<S: State> Cycle<S> {
+    /// The states before the cycle is entered, starting with `initial`.
+    pub fn prefix(&self, initial: S) -> Vec<S> {
+        states(initial).take(self.start).collect()
+    }
+}
+
+impl<S> Outcome<S> {
+    pub fn is_loop(&self) -> bool {
+        matches!(self, Outcome::Loops(_))
+    }
+}
+
+/// Every state from `initial` on, until the simulation ends.
+pub fn states<S: State>(initial: S) -> impl Iterator<Item = S> {
+    std::iter::successors(Some(initial), S::step)
+}
+
+fn step_on_cycle<S: State>(state: &S) -> S {
+    state
+        .step()
+        .expect("states on a cycle always have a successor")
+}
+
+/// Remembers every state seen. The fewest steps, but memory grows with the number of states.
+pub fn hashed<S: State + Hash>(initial: S) -> Outcome<S> {
+    let mut seen = HashMap::new();
+    let mut state = initial;
+    for steps in 0.. {
+        let Some(next) = state.step() else {
+            return Outcome::Halts { steps, last: state };
+        };
+        if let Some(start) = seen.insert(state.clone(), steps) {
+            return Outcome::Loops(Cycle {
+                start,
+                length: steps - start,
+                entry: state,
+            });
+        }
+        state = next;
+    }
+    unreachable!()
+}
+
+/// Floyd's tortoise and hare: constant memory.
+pub fn floyd<S: State>(initial: S) -> Outcome<S> {
+    // the hare runs at twice the speed, so it finds the end first if there is one
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    let mut hare_steps = 0;
+    loop {
+        for _ in 0..2 {
+            match hare.step() {
+                Some(next) => hare = next,
+                None => {
+                    return Outcome::Halts {
+                        steps: hare_steps,
+                        last: hare,
+                    }
+                }
+            }
+            hare_steps += 1;
+        }
+        tortoise = step_on_cycle(&tortoise);
+        if tortoise == hare {
+            break;
+        }
+    }
+
+    // the hare is now a multiple of the cycle length ahead of a tortoise back at the start, so
+    // they meet at the start of the cycle
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step_on_cycle(&tortoise);
+        hare = step_on_cycle(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step_on_cycle(&tortoise);
+    while tortoise != hare {
+        hare = step_on_cycle(&hare);
+        length += 1;
+    }
+
+    Outcome::Loops(Cycle {
+        start,
+        length,
+        entry: tortoise,
+    })
+}
+
+/// Brent's algorithm: constant memory, and usually fewer steps than [`floyd`].
+pub fn brent<S: State>(initial: S) -> Outcome<S> {
+    // the tortoise teleports to the hare at every power of two, so the hare eventually laps it
+    let mut power = 1;
+    let mut length = 0;
+    let mut tortoise = initial.clone();
+    let mut hare_steps = 0;
+    let mut hare = initial.clone();
+    loop {
+        match hare.step() {
+            Some(next) => hare = next,
+            None => {
+                return Outcome::Halts {
+                    steps: hare_steps,
+                    last: hare,
+                }
+            }
+        }
+        hare_steps += 1;
+        length += 1;
+        if tortoise == hare {
+            break;
+        }
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+    }
+
+    // with the hare a cycle length ahead, they meet at the start of the cycle
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..length {
+        hare = step_on_cycle(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step_on_cycle(&tortoise);
+        hare = step_on_cycle(&hare);
+        start += 1;
+    }
+
+    Outcome::Loops(Cycle {
+        start,
+        length,
+        entry: tortoise,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    /// `x -> x * x + 1 mod m`, stopping at `stop`.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Square {
+        x: u64,
+        modulus: u64,
+        stop: Option<u64>,
+    }
+
+    impl State for Square {
+        fn step(&self) -> Option<Self> {
+            (Some(self.x) != self.stop).then(|| Self {
+                x: (self.x * self.x + 1) % self.modulus,
+                ..*self
+            })
+        }
+    }
+
+    fn square(x: u64, modulus: u64, stop: Option<u64>) -> Square {
+        Square { x, modulus, stop }
+    }
+
+    type Detector = fn(Square) -> Outcome<Square>;
+
+    // 3 -> 10 -> 6 -> 18 -> 2 -> 5 -> 7 -> 12 -> 12 (mod 19)
+    #[test_case(hashed ; "hashed detector")]
+    #[test_case(floyd ; "floyd detector")]
+    #[test_case(brent ; "brent detector")]
+    fn self_loop(detect: Detector) {
+        let Outcome::Loops(cycle) = detect(square(3, 19, None)) else {
+            panic!("expected a loop");
+        };
+        assert_eq!((7, 1, 12), (cycle.start, cycle.length, cycle.entry.x));
+        let prefix: Vec<u64> = cycle
+            .prefix(square(3, 19, None))
+            .iter()
+            .map(|state| state.x)
+            .collect();
+        assert_eq!(vec![3, 10, 6, 18, 2, 5, 7], prefix);
+    }
+
+    #[test_case(hashed ; "hashed detector")]
+    #[test_case(floyd ; "floyd detector")]
+    #[test_case(brent ; "brent detector")]
+    fn agrees_with_brute_force(detect: Detector) {
+        for modulus in [7, 100, 1000, 4096] {
+            let initial = square(0, modulus, None);
+            let all: Vec<u64> = states(initial.clone())
+                .take(modulus as usize + 1)
+                .map(|state| state.x)
+                .collect();
+            // there are at most `modulus` distinct states, so the first one to come round again
+            // is the start of the cycle
+            let again = |idx: usize| all[idx + 1..].iter().position(|&x| x == all[idx]);
+            let start = (0..all.len()).find(|&idx| again(idx).is_some()).unwrap();
+            let length = again(start).unwrap() + 1;
+
+            let Outcome::Loops(cycle) = detect(initial) else {
+                panic!("expected a loop mod {modulus}");
+            };
+            assert_eq!(
+                (start, length),
+                (cycle.start, cycle.length),
+                "mod {modulus}"
+            );
+        }
+    }
+
+    #[test_case(hashed ; "hashed detector")]
+    #[test_case(floyd ; "floyd detector")]
+    #[test_case(brent ; "brent detector")]
+    fn halts(detect: Detector) {
+        assert_eq!(
+            Outcome::Halts {
+                steps: 2,
+                last: square(6, 19, Some(6))
+            },
+            detect(square(3, 19, Some(6)))
+        );
+        assert_eq!(
+            Outcome::Halts {
+                steps: 0,
+                last: square(3, 19, Some(3))
+            },
+            detect(square(3, 19, Some(3)))
+        );
+    }
+}
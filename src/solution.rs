@@ -0,0 +1,154 @@
+use std::fmt;
+
+use crate::{
+    answer::Answer,
+    day1, day2, day3, day4, day5, day6, day7,
+    parse::{Mode, ParseError},
+    report::Table,
+    visualize::Picture,
+};
+
+/// A day's puzzle: its parsed input and the two parts that are solved from it.
+pub trait Solution: Sized + Send + Sync + 'static {
+    const DAY: u8;
+
+    fn parse(input: &str, mode: Mode) -> Result<Self, ParseError>;
+
+    fn part1(&self) -> Result<Answer, SolveError>;
+
+    fn part2(&self) -> Result<Answer, SolveError>;
+
+    /// The puzzle drawn to show how `part` is solved, for days that support `--visualize`.
+    fn visualize(&self, _part: Part) -> Option<Picture> {
+        None
+    }
+
+    /// Frames of a step by step playback, for days that support `--animate`.
+    fn animate(&self) -> Option<Box<dyn Iterator<Item = Picture> + '_>> {
+        None
+    }
+
+    /// Tables of the steps behind the answers, for days that support `--trace`.
+    fn trace(&self) -> Option<Vec<Table>> {
+        None
+    }
+}
+
+/// Why a part has no answer for an input that parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveError(pub String);
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, clap::ValueEnum)]
+pub enum Part {
+    #[value(name = "1")]
+    One,
+    #[value(name = "2")]
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// A parsed input with the concrete [`Solution`] type erased, so that days can be driven
+/// uniformly.
+pub trait Parsed: Send + Sync {
+    fn solve(&self, part: Part) -> Result<Answer, SolveError>;
+
+    fn visualize(&self, part: Part) -> Option<Picture>;
+
+    fn animate(&self) -> Option<Box<dyn Iterator<Item = Picture> + '_>>;
+
+    fn trace(&self) -> Option<Vec<Table>>;
+}
+
+impl<S: Solution> Parsed for S {
+    fn solve(&self, part: Part) -> Result<Answer, SolveError> {
+        match part {
+            Part::One => self.part1(),
+            Part::Two => self.part2(),
+        }
+    }
+
+    fn visualize(&self, part: Part) -> Option<Picture> {
+        Solution::visualize(self, part)
+    }
+
+    fn animate(&self) -> Option<Box<dyn Iterator<Item = Picture> + '_>> {
+        Solution::animate(self)
+    }
+
+    fn trace(&self) -> Option<Vec<Table>> {
+        Solution::trace(self)
+    }
+}
+
+/// An entry in the [`REGISTRY`].
+pub struct Day {
+    pub day: u8,
+    /// Slow days are skipped unless asked for.
+    pub slow: bool,
+    parse: ParseFn,
+}
+
+type ParseFn = fn(&str, Mode) -> Result<Box<dyn Parsed>, ParseError>;
+
+impl Day {
+    const fn new<S: Solution>(slow: bool) -> Self {
+        Self {
+            day: S::DAY,
+            slow,
+            parse: parse::<S>,
+        }
+    }
+
+    pub fn parse(&self, input: &str, mode: Mode) -> Result<Box<dyn Parsed>, ParseError> {
+        (self.parse)(input, mode)
+    }
+}
+
+fn parse<S: Solution>(input: &str, mode: Mode) -> Result<Box<dyn Parsed>, ParseError> {
+    Ok(Box::new(S::parse(input, mode)?))
+}
+
+/// Every solved day, in order.
+pub const REGISTRY: &[Day] = &[
+    Day::new::<day1::Lists>(false),
+    Day::new::<day2::Reports>(false),
+    Day::new::<day3::Program>(false),
+    Day::new::<day4::WordSearch>(false),
+    Day::new::<day5::Manual>(false),
+    Day::new::<day6::Map>(false),
+    Day::new::<day7::Calibration>(true),
+];
+
+pub fn find(day: u8) -> Option<&'static Day> {
+    REGISTRY.iter().find(|entry| entry.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_ordered() {
+        assert!(REGISTRY.windows(2).all(|days| days[0].day < days[1].day));
+    }
+}
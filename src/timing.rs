@@ -0,0 +1,116 @@
+use std::{fmt::Write, time::Duration};
+
+use crate::{runner::DayResult, solution::Part};
+
+const HEADER: [&str; 5] = ["day", "parse", "part 1", "part 2", "total"];
+
+/// Renders the parse and part timings of each day as a table, with a row of totals at the end.
+pub fn table(results: &[DayResult]) -> String {
+    let mut rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|result| {
+            vec![
+                result.day.to_string(),
+                format_duration(Some(result.parse_time)),
+                format_duration(part_time(result, Part::One)),
+                format_duration(part_time(result, Part::Two)),
+                format_duration(Some(result.total_time())),
+            ]
+        })
+        .collect();
+
+    let sum = |time: &dyn Fn(&DayResult) -> Option<Duration>| {
+        let times: Vec<Duration> = results.iter().filter_map(time).collect();
+        (!times.is_empty()).then(|| times.into_iter().sum())
+    };
+    rows.push(vec![
+        "total".to_string(),
+        format_duration(sum(&|result| Some(result.parse_time))),
+        format_duration(sum(&|result| part_time(result, Part::One))),
+        format_duration(sum(&|result| part_time(result, Part::Two))),
+        format_duration(sum(&|result| Some(result.total_time()))),
+    ]);
+
+    render(&HEADER, &rows)
+}
+
+/// Lays out `rows` under `header`, left aligning the first column and right aligning the rest.
+pub fn render(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    let header: Vec<String> = header.iter().map(|cell| cell.to_string()).collect();
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, width))| {
+                if column == 0 {
+                    format!("{cell:<width$}", width = *width)
+                } else {
+                    format!("{cell:>width$}", width = *width)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        let _ = writeln!(table, "{}", line.trim_end());
+    }
+    table
+}
+
+fn part_time(result: &DayResult, part: Part) -> Option<Duration> {
+    result
+        .parts
+        .iter()
+        .find(|result| result.part == part)
+        .map(|result| result.time)
+}
+
+pub fn format_duration(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!("{duration:.2?}"),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{answer::Answer, runner::PartResult};
+
+    fn result(day: u8, parse: u64, parts: &[(Part, u64)]) -> DayResult {
+        DayResult {
+            day,
+            parse_time: Duration::from_micros(parse),
+            parts: parts
+                .iter()
+                .map(|&(part, time)| PartResult {
+                    part,
+                    answer: Ok(Answer::Usize(0)),
+                    time: Duration::from_micros(time),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn totals() {
+        let results = [
+            result(1, 100, &[(Part::One, 20), (Part::Two, 30)]),
+            result(6, 1000, &[(Part::One, 2000)]),
+        ];
+        let expected = "\
+day       parse   part 1   part 2     total
+1      100.00µs  20.00µs  30.00µs  150.00µs
+6        1.00ms   2.00ms        -    3.00ms
+total    1.10ms   2.02ms  30.00µs    3.15ms
+";
+        assert_eq!(expected, table(&results));
+    }
+}
@@ -0,0 +1,122 @@
+//! Drawing grids for `--visualize`, ANSI colour rendering, and terminal playback for `--animate`.
+
+use std::{
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+use crate::grid::{Grid, Position};
+
+/// How to draw a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Plain,
+    /// Background detail, such as letters that aren't part of any match.
+    Dim,
+    /// Cells that are part of the answer.
+    Highlight,
+    /// Where something has been, such as the guard's path.
+    Trail,
+    /// Something moving, such as the guard.
+    Actor,
+    /// Something in the way.
+    Wall,
+    /// A proposed change, such as a new obstruction.
+    Candidate,
+}
+
+impl Style {
+    /// The SGR parameters that select this style.
+    fn code(self) -> Option<&'static str> {
+        match self {
+            Style::Plain => None,
+            Style::Dim => Some("2"),
+            Style::Highlight => Some("1;33"),
+            Style::Trail => Some("36"),
+            Style::Actor => Some("1;32"),
+            Style::Wall => Some("1"),
+            Style::Candidate => Some("1;31"),
+        }
+    }
+}
+
+/// `glyph` wrapped in the escape codes for `style`.
+pub fn paint(glyph: char, style: Style) -> String {
+    match style.code() {
+        Some(code) => format!("\x1b[{code}m{glyph}\x1b[0m"),
+        None => glyph.to_string(),
+    }
+}
+
+/// A drawing of a puzzle, with a glyph and style for every cell.
+pub type Picture = Grid<(char, Style)>;
+
+/// Draws `grid`, with `cell` picking each cell's glyph and style.
+pub fn draw<T>(grid: &Grid<T>, mut cell: impl FnMut(Position, &T) -> (char, Style)) -> Picture {
+    let cells = grid.iter().map(|(pos, value)| cell(pos, value)).collect();
+    Grid::from_vec(grid.width(), grid.height(), cells)
+}
+
+/// `picture` as ANSI coloured text, one row per line.
+pub fn ansi(picture: &Picture) -> String {
+    let mut text = String::new();
+    for (pos, &(glyph, style)) in picture.iter() {
+        text.push_str(&paint(glyph, style));
+        if pos.x as usize == picture.width() - 1 {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Plays `frames` in place on a terminal at `fps` frames per second.
+pub fn play(
+    out: &mut impl Write,
+    frames: impl Iterator<Item = String>,
+    fps: f64,
+) -> io::Result<()> {
+    let delay = Duration::from_secs_f64(1.0 / fps);
+    // hide the cursor and clear the screen, then redraw over the top of each frame
+    write!(out, "\x1b[?25l\x1b[2J")?;
+    let result = frames.into_iter().try_for_each(|frame| {
+        write!(out, "\x1b[H{frame}")?;
+        out.flush()?;
+        thread::sleep(delay);
+        Ok(())
+    });
+    write!(out, "\x1b[?25h")?;
+    out.flush()?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn draws_styles() {
+        let grid = parse::grid("ab\ncd\n", |_, c| c).unwrap();
+        let text = ansi(&draw(&grid, |pos, c| {
+            let style = if pos.x == pos.y {
+                Style::Highlight
+            } else {
+                Style::Plain
+            };
+            (*c, style)
+        }));
+        assert_eq!("\x1b[1;33ma\x1b[0mb\nc\x1b[1;33md\x1b[0m\n", text);
+    }
+
+    #[test]
+    fn plays_frames() {
+        let mut out = Vec::new();
+        let frames = ["1\n", "2\n"].into_iter().map(String::from);
+        play(&mut out, frames, 1000.0).unwrap();
+        assert_eq!(
+            "\x1b[?25l\x1b[2J\x1b[H1\n\x1b[H2\n\x1b[?25h",
+            String::from_utf8(out).unwrap()
+        );
+    }
+}